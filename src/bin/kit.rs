@@ -1,6 +1,14 @@
 use kit::kit_main;
-use std::error::Error;
+use std::process::ExitCode;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    kit_main()
+fn main() -> ExitCode {
+    match kit_main() {
+        Ok(()) => ExitCode::SUCCESS,
+        // Display rather than the Debug that returning the error would print, which shows the
+        // variant it came from instead of just the message.
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
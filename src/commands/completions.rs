@@ -0,0 +1,42 @@
+use std::io::{stdout, Write};
+
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
+
+use crate::{error::KitError, Cli};
+
+#[derive(Debug, Parser)]
+#[clap(about = "Print a shell completion script for kit.")]
+pub struct CompletionsCommand {
+    #[arg(help = "Shell to generate completions for")]
+    shell: Shell,
+}
+
+impl CompletionsCommand {
+    pub fn run(&self) -> Result<(), KitError> {
+        generate(self.shell, &mut stdout());
+        Ok(())
+    }
+}
+
+/// Write the completion script for `shell`, derived from the `Cli` parser so that it always covers
+/// the current subcommands and flags.
+fn generate(shell: Shell, buf: &mut impl Write) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_bash() {
+        let mut buf = vec![];
+        generate(Shell::Bash, &mut buf);
+        let script = String::from_utf8(buf).unwrap();
+        assert!(script.contains("pomo"));
+        assert!(script.contains("word"));
+    }
+}
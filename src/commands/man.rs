@@ -0,0 +1,54 @@
+use std::{
+    io::{stdout, Write},
+    path::PathBuf,
+};
+
+use clap::{CommandFactory, Parser};
+use clap_mangen::Man;
+
+use crate::{error::KitError, Cli};
+
+#[derive(Debug, Parser)]
+#[clap(about = "Render man pages for kit and its subcommands.")]
+pub struct ManCommand {
+    #[arg(
+        short,
+        long,
+        help = "Write one page per command (kit.1, kit-pomo.1, ...) into this directory instead of printing the kit page",
+        value_name = "DIR"
+    )]
+    out: Option<PathBuf>,
+}
+
+impl ManCommand {
+    pub fn run(&self) -> Result<(), KitError> {
+        match &self.out {
+            Some(out) => {
+                std::fs::create_dir_all(out)?;
+                clap_mangen::generate_to(Cli::command(), out)?;
+            }
+            None => render(&mut stdout())?,
+        }
+        Ok(())
+    }
+}
+
+/// Write the roff source of the top-level `kit` page.
+fn render(buf: &mut impl Write) -> std::io::Result<()> {
+    Man::new(Cli::command()).render(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mut buf = vec![];
+        render(&mut buf).unwrap();
+        let page = String::from_utf8(buf).unwrap();
+        assert!(page.starts_with(".ie"));
+        assert!(page.contains("kit\\-pomo"));
+        assert!(!page.contains("kit\\-man"));
+    }
+}
@@ -1,2 +1,4 @@
+pub mod completions;
+pub mod man;
 pub mod pomo;
 pub mod word;
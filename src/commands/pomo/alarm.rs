@@ -0,0 +1,243 @@
+use std::{ops::ControlFlow, path::PathBuf, time::Duration};
+
+use chrono::{Local, NaiveTime};
+use clap::Parser;
+use crossterm::event::Event;
+use notify_rust::Notification;
+use ratatui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    widgets, Frame,
+};
+use tokio::time::Instant;
+
+use super::{
+    format_hms, gauge_color, help_table, load_keys, next_deadline, parse_clock_time,
+    progress_gauge, progress_ratio, DisplayOptions, PomoInput, HELP_WIDTH, TICK_RATE,
+};
+use crate::{
+    config::Config,
+    error::KitError,
+    sound::{self, Sound},
+    theme::{self, Theme},
+    tui::{run_app, App},
+    with_tui::WithTui,
+};
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Count down to a time on the clock and signal when it comes. Once it goes off, any key \
+             dismisses it."
+)]
+pub struct AlarmCommand {
+    #[arg(
+        help = "When to go off, in local time; a time already past today means tomorrow",
+        value_name = "HH:MM",
+        value_parser = parse_clock_time
+    )]
+    time: NaiveTime,
+
+    #[arg(long, help = "Show a desktop notification when the alarm goes off")]
+    notify: bool,
+
+    #[arg(
+        long,
+        help = "Sound file to play when the alarm goes off (needs the `sound` feature)",
+        value_name = "PATH"
+    )]
+    sound: Option<PathBuf>,
+
+    #[arg(long, help = "Ring the terminal bell when the alarm goes off")]
+    bell: bool,
+
+    #[arg(
+        long,
+        help = "Draw the TUI without colors (also set by the NO_COLOR variable)"
+    )]
+    no_color: bool,
+
+    #[arg(
+        long,
+        help = "Color the TUI with a built-in theme instead of the config file's: `default`, \
+                `solarized`, `mono` or `high-contrast`",
+        value_name = "NAME",
+        value_parser = theme::parse_preset
+    )]
+    theme: Option<Theme>,
+
+    #[arg(
+        long,
+        help = "Draw the TUI with plain ASCII (also set by a TERM of `dumb` or `vt*`)"
+    )]
+    ascii_only: bool,
+}
+
+impl AlarmCommand {
+    pub async fn run(&self) -> Result<(), KitError> {
+        let config = Config::load()?;
+        let options = DisplayOptions::new(
+            config
+                .theme
+                .overridden_by(self.theme)
+                .with_ascii_only(self.ascii_only),
+            load_keys(&config)?,
+            self.no_color,
+            false,
+        );
+        let sound = self.sound.as_deref().map(Sound::load).transpose()?;
+        let started = Instant::now();
+        let deadline = next_deadline(self.time);
+        log::info!(
+            "alarm set for {} (in {})",
+            self.time.format("%H:%M"),
+            format_hms(deadline - started)
+        );
+        let mut alarm = Alarm {
+            command: self,
+            sound,
+            started,
+            deadline,
+            gone_off: false,
+            show_help: false,
+            options,
+        };
+        run_app(&mut alarm).await
+    }
+}
+
+/// Waits for the deadline, then keeps showing that it's time until a key is pressed.
+struct Alarm<'a> {
+    command: &'a AlarmCommand,
+    sound: Option<Sound>,
+    started: Instant,
+    deadline: Instant,
+    gone_off: bool,
+    show_help: bool,
+    options: DisplayOptions,
+}
+
+impl Alarm<'_> {
+    fn go_off(&mut self) -> Result<(), KitError> {
+        log::info!("alarm went off");
+        self.gone_off = true;
+        if let Some(sound) = &self.sound {
+            sound.spawn_play();
+        }
+        if self.command.bell {
+            sound::bell()?;
+        }
+        if self.command.notify {
+            let shown = Notification::new()
+                .summary("Time!")
+                .body(&format!("It's {}", self.command.time.format("%H:%M")))
+                .show();
+            if let Err(e) = shown {
+                log::debug!("failed to show notification: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WithTui for Alarm<'_> {}
+
+impl App for Alarm<'_> {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>) -> Result<(), KitError> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        render_alarm(
+            f,
+            self.command.time,
+            self.deadline - self.started,
+            remaining,
+            self.show_help,
+            &self.options,
+        );
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<ControlFlow<()>, KitError> {
+        match self.options.keys.input(event) {
+            Some(PomoInput::Resize) => {}
+            Some(PomoInput::Help) if !self.gone_off => self.show_help = !self.show_help,
+            Some(PomoInput::Quit) => return Ok(ControlFlow::Break(())),
+            _ if self.gone_off && matches!(event, Event::Key(_)) => {
+                log::info!("alarm dismissed");
+                return Ok(ControlFlow::Break(()));
+            }
+            _ => {}
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    fn tick(&mut self) -> Result<ControlFlow<()>, KitError> {
+        if !self.gone_off && Instant::now() >= self.deadline {
+            self.go_off()?;
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    fn tick_rate(&self) -> Option<Duration> {
+        Some(TICK_RATE)
+    }
+
+    fn ascii_only(&self) -> bool {
+        self.options.theme.ascii_only
+    }
+
+    fn title(&self) -> Option<String> {
+        let status = if self.gone_off {
+            "Time!".to_string()
+        } else {
+            format_hms(self.deadline.saturating_duration_since(Instant::now()))
+        };
+        Some(format!("kit alarm - {}", status))
+    }
+}
+
+/// Draw the clock above a gauge of how much of the wait has gone by since the alarm was set.
+fn render_alarm<B: Backend>(
+    f: &mut Frame<B>,
+    time: NaiveTime,
+    total: Duration,
+    remaining: Duration,
+    show_help: bool,
+    options: &DisplayOptions,
+) {
+    let theme = &options.theme;
+    let color = gauge_color(true, 1.0 - progress_ratio(total, remaining));
+    let title = format!("Alarm: {}", time.format("%H:%M"));
+    let gauge = progress_gauge(theme, &title, total, remaining, false, color).label(
+        if remaining.is_zero() {
+            "Time!".to_string()
+        } else {
+            format_hms(remaining)
+        },
+    );
+    let now = Local::now().format("%H:%M:%S").to_string();
+    let clock = widgets::Paragraph::new(now)
+        .alignment(Alignment::Center)
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .block(theme.block().title("Now"));
+    let vertical_margin = f.size().height.saturating_sub(11).div_euclid(4);
+    let chunks = Layout::default()
+        .horizontal_margin(4)
+        .vertical_margin(vertical_margin)
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(4),
+            Constraint::Min(0),
+        ])
+        .split(f.size());
+    f.render_widget(clock, chunks[0]);
+    f.render_widget(gauge, chunks[1]);
+    if show_help {
+        let help_area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(HELP_WIDTH), Constraint::Min(0)])
+            .split(chunks[2]);
+        f.render_widget(help_table(&[], options), help_area[0]);
+    }
+}
@@ -0,0 +1,82 @@
+use std::io::Write;
+
+use serde::Serialize;
+
+/// A change in timer state, printed as one JSON object per line by `--json-events`. The `event`
+/// field names the variant, e.g. `{"event":"tick","segment":"Work","remaining_secs":1471}`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PomoEvent {
+    Start {
+        segment: &'static str,
+        duration_secs: u64,
+    },
+    /// Sent once per second of running time, so not while paused.
+    Tick {
+        segment: &'static str,
+        remaining_secs: u64,
+    },
+    Pause {
+        segment: &'static str,
+        remaining_secs: u64,
+    },
+    Resume {
+        segment: &'static str,
+        remaining_secs: u64,
+    },
+    Skip {
+        segment: &'static str,
+        remaining_secs: u64,
+    },
+    /// Sent for the segment being left, before the `start` of the one before it.
+    Previous {
+        segment: &'static str,
+        remaining_secs: u64,
+    },
+    Complete {
+        segment: &'static str,
+    },
+    Quit {
+        segment: &'static str,
+        remaining_secs: u64,
+    },
+    /// The `--reminder` countdown ran out.
+    Reminder,
+}
+
+impl PomoEvent {
+    /// Write the event as a line of JSON and flush, so that a reader on the other end of a pipe
+    /// sees it straight away.
+    pub fn write(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(self)?;
+        line.push(b'\n');
+        out.write_all(&line)?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write() {
+        let mut buf = vec![];
+        PomoEvent::Tick {
+            segment: "Work",
+            remaining_secs: 1471,
+        }
+        .write(&mut buf)
+        .unwrap();
+        PomoEvent::Complete { segment: "Work" }
+            .write(&mut buf)
+            .unwrap();
+        PomoEvent::Reminder.write(&mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "{\"event\":\"tick\",\"segment\":\"Work\",\"remaining_secs\":1471}\n\
+             {\"event\":\"complete\",\"segment\":\"Work\"}\n\
+             {\"event\":\"reminder\"}\n"
+        );
+    }
+}
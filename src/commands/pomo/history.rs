@@ -0,0 +1,204 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::csv::csv_row;
+
+/// The first line of a `--csv-log` file.
+const CSV_HEADER: &str = "date,time,segment,planned_minutes,actual_seconds,completed\n";
+
+/// How a logged segment ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Completed,
+    Skipped,
+    Quit,
+}
+
+/// One line of the pomodoro history log.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub timestamp: DateTime<Local>,
+    pub segment: String,
+    /// The `--tasks` label of a work segment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    pub planned_secs: u64,
+    pub elapsed_secs: u64,
+    /// Time spent paused, which is not part of `elapsed_secs`. Missing from older logs.
+    #[serde(default)]
+    pub paused_secs: u64,
+    pub outcome: Outcome,
+}
+
+impl HistoryRecord {
+    pub fn new(
+        segment: &str,
+        task: Option<&str>,
+        planned: Duration,
+        elapsed: Duration,
+        paused: Duration,
+        outcome: Outcome,
+    ) -> Self {
+        Self {
+            timestamp: Local::now(),
+            segment: segment.to_string(),
+            task: task.map(str::to_string),
+            planned_secs: planned.as_secs(),
+            elapsed_secs: elapsed.as_secs(),
+            paused_secs: paused.as_secs(),
+            outcome,
+        }
+    }
+}
+
+/// The history log lives at `<data dir>/kit/pomo_history.jsonl`, e.g.
+/// `~/.local/share/kit/pomo_history.jsonl` on Linux.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("kit").join("pomo_history.jsonl"))
+}
+
+/// Append a record to the log at `path`, creating the file and its parent directories if needed.
+///
+/// The log holds one JSON object per line, so appending never requires parsing what is already
+/// there. Each record goes out in a single write to a file opened in append mode, which keeps
+/// lines from concurrent `kit pomo` processes from interleaving.
+pub fn append(path: &Path, record: &HistoryRecord) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Append a record to the CSV file at `path` as a row, starting the file with the header row
+/// if it's new or empty. The row is flushed straight away, so an interrupted session keeps every
+/// segment that ended before it.
+pub fn append_csv(path: &Path, record: &HistoryRecord) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut lines = if file.metadata()?.len() == 0 {
+        CSV_HEADER.to_string()
+    } else {
+        String::new()
+    };
+    lines.push_str(&csv_row(&[
+        &record.timestamp.format("%Y-%m-%d").to_string(),
+        &record.timestamp.format("%H:%M:%S").to_string(),
+        &record.segment,
+        &(record.planned_secs / 60).to_string(),
+        &record.elapsed_secs.to_string(),
+        &(record.outcome == Outcome::Completed).to_string(),
+    ]));
+    file.write_all(lines.as_bytes())?;
+    file.flush()
+}
+
+/// Read every record in the log at `path`. A line that doesn't parse is skipped with a warning
+/// rather than hiding the rest of the history.
+pub fn read(path: &Path) -> std::io::Result<Vec<HistoryRecord>> {
+    let contents = fs::read_to_string(path)?;
+    let records = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(i, line)| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                log::warn!("skipping line {} of {}: {}", i + 1, path.display(), e);
+                None
+            }
+        })
+        .collect();
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_creates_and_appends() {
+        let dir = std::env::temp_dir().join(format!("kit-history-test-{}", std::process::id()));
+        let path = dir.join("nested").join("history.jsonl");
+        let _ = fs::remove_dir_all(&dir);
+        let completed = HistoryRecord::new(
+            "Work",
+            None,
+            Duration::from_secs(1500),
+            Duration::from_secs(1500),
+            Duration::ZERO,
+            Outcome::Completed,
+        );
+        let quit = HistoryRecord::new(
+            "Work",
+            Some("Write the report"),
+            Duration::from_secs(1500),
+            Duration::from_secs(600),
+            Duration::from_secs(90),
+            Outcome::Quit,
+        );
+        append(&path, &completed).unwrap();
+        append(&path, &quit).unwrap();
+        assert_eq!(read(&path).unwrap(), vec![completed.clone(), quit.clone()]);
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"truncated\n")
+            .unwrap();
+        append(&path, &completed).unwrap();
+        assert_eq!(
+            read(&path).unwrap(),
+            vec![completed.clone(), quit, completed]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_append_csv() {
+        let dir = std::env::temp_dir().join(format!("kit-csv-log-test-{}", std::process::id()));
+        let path = dir.join("pomo.csv");
+        let _ = fs::remove_dir_all(&dir);
+        let work = HistoryRecord::new(
+            "Work",
+            None,
+            Duration::from_secs(1500),
+            Duration::from_secs(1500),
+            Duration::ZERO,
+            Outcome::Completed,
+        );
+        let skipped = HistoryRecord::new(
+            "Short break",
+            None,
+            Duration::from_secs(300),
+            Duration::from_secs(42),
+            Duration::ZERO,
+            Outcome::Skipped,
+        );
+        append_csv(&path, &work).unwrap();
+        append_csv(&path, &skipped).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER.trim_end());
+        let timestamp = work.timestamp.format("%Y-%m-%d,%H:%M:%S");
+        assert_eq!(lines[1], format!("{},Work,25,1500,true", timestamp));
+        assert!(lines[2].ends_with(",Short break,5,42,false"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use super::PomoInput;
+use crate::config::{KeySpecs, PomoKeys};
+
+/// Which key does what in the pomo TUI and `--plain`. Ctrl-C quits whatever the config says, so
+/// there is always a way out.
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    inputs: HashMap<KeyEvent, PomoInput>,
+    /// The configured keys for each action in order, for the help
+    bindings: Vec<(PomoInput, Vec<KeyEvent>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&PomoKeys::default()).expect("the default keys are valid and distinct")
+    }
+}
+
+impl Keymap {
+    pub fn new(config: &PomoKeys) -> Result<Self, String> {
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        let mut inputs = HashMap::from([(ctrl_c, PomoInput::Quit)]);
+        let mut bindings = vec![];
+        // Each action's keys in the config, and the keys it gets if there are none
+        let actions = [
+            (PomoInput::Help, &config.help, ["h", "?"].as_slice()),
+            (PomoInput::Quit, &config.quit, &["q", "esc"]),
+            (PomoInput::Pause, &config.pause, &["space"]),
+            (PomoInput::Skip, &config.skip, &["s"]),
+            (PomoInput::Previous, &config.previous, &["b"]),
+            (PomoInput::Label, &config.label, &["l"]),
+            (PomoInput::EndTimes, &config.end_times, &["e"]),
+        ];
+        for (input, configured, defaults) in actions {
+            let keys = match configured {
+                Some(specs) => specs
+                    .specs()
+                    .iter()
+                    .map(|spec| parse_key(spec))
+                    .collect::<Result<Vec<_>, _>>()?,
+                None => defaults
+                    .iter()
+                    .map(|spec| parse_key(spec).unwrap())
+                    .collect(),
+            };
+            for &key in &keys {
+                match inputs.insert(key, input) {
+                    Some(other) if other != input => {
+                        return Err(format!(
+                            "{} is bound to both {} and {}",
+                            format_key(key),
+                            action_name(other),
+                            action_name(input)
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+            bindings.push((input, keys));
+        }
+        Ok(Self { inputs, bindings })
+    }
+
+    /// Translate a key press or a resize. Shift is ignored for characters, since it's already in
+    /// which character arrived.
+    pub fn input(&self, event: Event) -> Option<PomoInput> {
+        match event {
+            Event::Key(key) => self.inputs.get(&normalize(key)).copied(),
+            Event::Mouse(_) => None,
+            Event::Resize(_, _) => Some(PomoInput::Resize),
+        }
+    }
+
+    /// The keys bound to each action, spelled the way the config file does, for
+    /// `--print-config`. Ctrl-C is left out, since it quits whatever the config says.
+    pub fn to_config(&self) -> PomoKeys {
+        let mut config = PomoKeys::default();
+        for (input, keys) in &self.bindings {
+            let specs = Some(KeySpecs::Many(
+                keys.iter().map(|&key| key_spec(key)).collect(),
+            ));
+            match input {
+                PomoInput::Help => config.help = specs,
+                PomoInput::Quit => config.quit = specs,
+                PomoInput::Pause => config.pause = specs,
+                PomoInput::Skip => config.skip = specs,
+                PomoInput::Previous => config.previous = specs,
+                PomoInput::Label => config.label = specs,
+                PomoInput::EndTimes => config.end_times = specs,
+                PomoInput::Resize => {}
+            }
+        }
+        config
+    }
+
+    /// The keys for `input` as the help shows them, like `q|<Esc>`.
+    pub fn describe(&self, input: PomoInput) -> String {
+        self.bindings
+            .iter()
+            .filter(|(bound, _)| *bound == input)
+            .flat_map(|(_, keys)| keys.iter().map(|&key| format_key(key)))
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+}
+
+fn action_name(input: PomoInput) -> &'static str {
+    match input {
+        PomoInput::Help => "help",
+        PomoInput::Pause => "pause",
+        PomoInput::Quit => "quit",
+        PomoInput::Skip => "skip",
+        PomoInput::Previous => "previous",
+        PomoInput::Label => "label",
+        PomoInput::EndTimes => "end_times",
+        PomoInput::Resize => "resize",
+    }
+}
+
+fn normalize(key: KeyEvent) -> KeyEvent {
+    match key.code {
+        KeyCode::Char(_) => KeyEvent::new(key.code, key.modifiers - KeyModifiers::SHIFT),
+        _ => key,
+    }
+}
+
+/// Parse a key spec like `q`, `space`, `ctrl+c` or `alt+left`. Modifiers come first, each
+/// followed by `+`. Names ignore case, but a single character stands for itself, so `Q` is a
+/// capital Q.
+pub fn parse_key(spec: &str) -> Result<KeyEvent, String> {
+    let (modifier_names, name) = match spec.rsplit_once('+') {
+        // The key itself is `+`, as in `+` or `ctrl++`.
+        Some((rest, "")) => (rest.strip_suffix('+').unwrap_or(rest), "+"),
+        Some((modifier_names, name)) => (modifier_names, name),
+        None => ("", spec),
+    };
+    let mut modifiers = KeyModifiers::NONE;
+    for modifier in modifier_names.split('+').filter(|m| !m.is_empty()) {
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return Err(format!("unknown modifier {:?} in key {:?}", modifier, spec)),
+        };
+    }
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match name.to_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            lower => match lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => return Err(format!("unknown key {:?}", spec)),
+            },
+        },
+    };
+    Ok(normalize(KeyEvent::new(code, modifiers)))
+}
+
+/// Spell a key the way `parse_key` reads it, e.g. `s`, `space` or `ctrl+d`.
+fn key_spec(key: KeyEvent) -> String {
+    let name = match key.code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("f{}", n),
+        code => format!("{:?}", code).to_lowercase(),
+    };
+    let mut prefix = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str("ctrl+");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("alt+");
+    }
+    if key.modifiers.contains(KeyModifiers::SHIFT) {
+        prefix.push_str("shift+");
+    }
+    prefix + &name
+}
+
+/// Show a key the way the help tables do, e.g. `s`, `<Space>` or `^D`.
+fn format_key(key: KeyEvent) -> String {
+    let name = match key.code {
+        KeyCode::Char(' ') => "<Space>".to_string(),
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            c.to_uppercase().to_string()
+        }
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("<F{}>", n),
+        code => format!("<{:?}>", code),
+    };
+    let mut prefix = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push('^');
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("M-");
+    }
+    if key.modifiers.contains(KeyModifiers::SHIFT) {
+        prefix.push_str("S-");
+    }
+    prefix + &name
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::KeySpecs;
+
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(
+            parse_key("ctrl+c"),
+            Ok(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key("Space"),
+            Ok(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key("ctrl++"),
+            Ok(KeyEvent::new(KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key("alt+F5"),
+            Ok(KeyEvent::new(KeyCode::F(5), KeyModifiers::ALT))
+        );
+        assert_eq!(
+            parse_key("hyper+x"),
+            Err("unknown modifier \"hyper\" in key \"hyper+x\"".into())
+        );
+        assert_eq!(parse_key("f13"), Err("unknown key \"f13\"".into()));
+    }
+
+    #[test]
+    fn test_key_spec() {
+        for spec in [
+            "s",
+            "space",
+            "esc",
+            "ctrl++",
+            "alt+f5",
+            "ctrl+alt+pageup",
+            "?",
+        ] {
+            assert_eq!(key_spec(parse_key(spec).unwrap()), spec);
+        }
+    }
+
+    #[test]
+    fn test_default_keymap() {
+        let keys = Keymap::default();
+        assert_eq!(
+            keys.input(key(KeyCode::Char('?'), KeyModifiers::SHIFT)),
+            Some(PomoInput::Help)
+        );
+        assert_eq!(
+            keys.input(key(KeyCode::Esc, KeyModifiers::NONE)),
+            Some(PomoInput::Quit)
+        );
+        assert_eq!(
+            keys.input(key(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(PomoInput::Quit)
+        );
+        assert_eq!(
+            keys.input(key(KeyCode::Char('x'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(keys.describe(PomoInput::Quit), "q|<Esc>");
+        assert_eq!(keys.describe(PomoInput::Pause), "<Space>");
+    }
+
+    #[test]
+    fn test_configured_keymap() {
+        let config = PomoKeys {
+            pause: Some(KeySpecs::One("p".to_string())),
+            skip: Some(KeySpecs::Many(vec!["j".to_string(), "ctrl+n".to_string()])),
+            ..PomoKeys::default()
+        };
+        let keys = Keymap::new(&config).unwrap();
+        assert_eq!(
+            keys.input(key(KeyCode::Char('p'), KeyModifiers::NONE)),
+            Some(PomoInput::Pause)
+        );
+        assert_eq!(
+            keys.input(key(KeyCode::Char(' '), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            keys.input(key(KeyCode::Char('s'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(keys.describe(PomoInput::Skip), "j|^N");
+        let clash = PomoKeys {
+            help: Some(KeySpecs::One("s".to_string())),
+            ..PomoKeys::default()
+        };
+        assert_eq!(
+            Keymap::new(&clash).unwrap_err(),
+            "s is bound to both help and skip"
+        );
+        let ctrl_c = PomoKeys {
+            pause: Some(KeySpecs::One("ctrl+c".to_string())),
+            ..PomoKeys::default()
+        };
+        assert_eq!(
+            Keymap::new(&ctrl_c).unwrap_err(),
+            "^C is bound to both quit and pause"
+        );
+    }
+}
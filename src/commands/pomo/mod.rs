@@ -0,0 +1,3766 @@
+mod alarm;
+mod events;
+mod history;
+mod keys;
+mod resume;
+mod sequence;
+mod stats;
+mod tasks;
+mod timer;
+
+use std::{
+    io::{stdin, stdout, IsTerminal, Write},
+    iter::{once, repeat_n},
+    ops::ControlFlow,
+    path::PathBuf,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, Timelike};
+use clap::{Parser, Subcommand, ValueEnum};
+use crossterm::{
+    event::{
+        Event, EventStream, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
+    terminal::{Clear, ClearType},
+    ExecutableCommand,
+};
+use futures::{FutureExt, StreamExt};
+use itertools::Itertools;
+use notify_rust::Notification;
+use ratatui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets, Frame,
+};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+    time::{self, Instant, MissedTickBehavior},
+};
+
+use crate::{
+    config::{Config, PomoConfig, ResolvedPomoConfig},
+    duration,
+    error::KitError,
+    sound::{self, Sound},
+    theme::{self, Theme},
+    tui::{run_app, App},
+    with_tui::{self, RawModeGuard, WithTui},
+};
+pub use alarm::AlarmCommand;
+use events::PomoEvent;
+use history::{HistoryRecord, Outcome};
+use keys::Keymap;
+use resume::SavedState;
+use sequence::parse_sequence;
+use stats::StatsCommand;
+use tasks::read_tasks;
+pub use timer::TimerCommand;
+
+/// A segment and its length in minutes. Work segments from `--tasks` are labeled with the task.
+#[derive(Clone, Debug)]
+enum PomoSegment {
+    Work(u64, Option<String>),
+    ShortBreak(u64),
+    LongBreak(u64),
+}
+
+impl PomoSegment {
+    fn duration(&self) -> Duration {
+        let minutes = match self {
+            PomoSegment::Work(minutes, _) => minutes,
+            PomoSegment::ShortBreak(minutes) => minutes,
+            PomoSegment::LongBreak(minutes) => minutes,
+        };
+        Duration::from_secs(minutes.saturating_mul(60))
+    }
+
+    /// What kind of segment this is, regardless of any label, as written to the history log and
+    /// the `--json-events` output.
+    fn kind(&self) -> &'static str {
+        match self {
+            PomoSegment::Work(..) => "Work",
+            PomoSegment::ShortBreak(_) => "Short break",
+            PomoSegment::LongBreak(_) => "Long break",
+        }
+    }
+
+    fn task(&self) -> Option<&str> {
+        match self {
+            PomoSegment::Work(_, label) => label.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> From<&'a PomoSegment> for &'a str {
+    fn from(segment: &'a PomoSegment) -> Self {
+        segment.task().unwrap_or(segment.kind())
+    }
+}
+
+impl std::fmt::Display for PomoSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&str>::into(self))
+    }
+}
+
+const DEFAULT_TIME: u64 = 25;
+const DEFAULT_BREAK: u64 = 5;
+const DEFAULT_LONG_BREAK: u64 = 15;
+const DEFAULT_N_POMOS: u64 = 3;
+/// Segments longer than this are allowed, but probably a typo, so they get a warning.
+const MAX_SEGMENT_MINUTES: u64 = 24 * 60;
+
+/// Flags for segment lengths and counts default to the `[pomo]` section of the config file, and
+/// failing that to the `DEFAULT_*` constants.
+#[derive(Debug, Parser)]
+#[clap(about = "Run pomodoro timers. Press 'h' to see help for keyboard shortcuts while running.")]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct PomoCommand {
+    #[command(subcommand)]
+    subcommand: Option<PomoSubcommand>,
+
+    #[arg(
+        short,
+        long,
+        help = "Length of work periods [default: 25]",
+        value_name = "MINUTES",
+        value_parser = parse_minutes
+    )]
+    time: Option<u64>,
+
+    #[arg(
+        short,
+        long,
+        help = "Length of break periods [default: 5]",
+        value_name = "MINUTES",
+        value_parser = parse_minutes
+    )]
+    break_: Option<u64>,
+
+    #[arg(
+        short,
+        long,
+        help = "Length of long break periods [default: 15]",
+        value_name = "MINUTES",
+        value_parser = parse_minutes
+    )]
+    long_break: Option<u64>,
+
+    #[arg(
+        short,
+        long,
+        help = "Number of work periods per long break [default: 3]",
+        value_name = "NUMBER"
+    )]
+    n_pomos: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Alternate work and short breaks with no long break; --n-pomos is ignored",
+        conflicts_with_all = ["long_break", "sequence"]
+    )]
+    no_long_break: bool,
+
+    #[arg(
+        long,
+        help = "Run these segments instead, e.g. `w25,b5,w25,lb20` or `3x(w25,b5),lb20`",
+        value_name = "SPEC"
+    )]
+    sequence: Option<String>,
+
+    #[arg(
+        long,
+        help = "Run the tasks in this file (`-` for stdin), one `MINUTES TASK NAME` a line",
+        value_name = "PATH",
+        conflicts_with_all = ["sequence", "time", "stopwatch"]
+    )]
+    tasks: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "File to append the work segment history to [default: ~/.local/share/kit/pomo_history.jsonl]",
+        value_name = "PATH"
+    )]
+    log: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Also append a row for each segment, work or break, to this CSV file for \
+                spreadsheets",
+        value_name = "PATH",
+        conflicts_with = "stopwatch"
+    )]
+    csv_log: Option<PathBuf>,
+
+    #[arg(long, help = "Show a desktop notification when a segment finishes")]
+    notify: bool,
+
+    #[arg(
+        long,
+        help = "Sound file to play when a segment finishes (needs the `sound` feature)",
+        value_name = "PATH"
+    )]
+    sound: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Sound file to play when a work segment finishes, instead of --sound",
+        value_name = "PATH"
+    )]
+    work_sound: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Sound file to play when a break finishes, instead of --sound",
+        value_name = "PATH"
+    )]
+    break_sound: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Volume to play the sounds at, from 0 to 100",
+        value_name = "PERCENT",
+        default_value_t = 100,
+        value_parser = clap::value_parser!(u8).range(0..=100)
+    )]
+    volume: u8,
+
+    #[arg(
+        long,
+        help = "How many times to play the sound each time a segment finishes",
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    alert_repeat: u32,
+
+    #[arg(long, help = "Ring the terminal bell when a segment finishes")]
+    bell: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Stop after this many full cycles [default: run until quit]",
+        value_name = "NUMBER"
+    )]
+    repeat: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Stop at this local time, cutting the last segment short to end on it",
+        value_name = "HH:MM",
+        value_parser = parse_clock_time,
+        conflicts_with = "stopwatch"
+    )]
+    until: Option<NaiveTime>,
+
+    #[arg(
+        long,
+        help = "Count up from zero instead of running pomodoros; 's' records a lap"
+    )]
+    stopwatch: bool,
+
+    #[arg(
+        long,
+        help = "Print a one-line status instead of drawing the TUI, e.g. for a tmux status bar; \
+                the default when not run in a terminal",
+        conflicts_with = "stopwatch"
+    )]
+    plain: bool,
+
+    #[arg(
+        long,
+        help = "Print each timer event as a line of JSON on stdout instead of drawing the TUI",
+        conflicts_with = "stopwatch"
+    )]
+    json_events: bool,
+
+    #[arg(
+        long,
+        help = "Run one work segment with nothing on screen but its gauge, then print how long it \
+                was and exit",
+        conflicts_with_all = ["sequence", "tasks", "stopwatch", "json_events", "resume", "big", "mouse"]
+    )]
+    quiet: bool,
+
+    #[arg(
+        long,
+        help = "Print the segments of one cycle and their total length, then exit",
+        conflicts_with = "stopwatch"
+    )]
+    show_schedule: bool,
+
+    #[arg(
+        long,
+        help = "Print the settings this session would run with as a config file section, then exit",
+        conflicts_with_all = ["stopwatch", "show_schedule"]
+    )]
+    print_config: bool,
+
+    #[arg(
+        long,
+        help = "Check the config file and flags without starting a session, printing any \
+                warnings; exits with an error if something is wrong",
+        conflicts_with_all = ["stopwatch", "show_schedule", "print_config"]
+    )]
+    check: bool,
+
+    #[arg(
+        long,
+        help = "Flash the screen when a segment finishes",
+        conflicts_with_all = ["plain", "json_events", "stopwatch"]
+    )]
+    flash: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Precision::Seconds,
+        help = "How finely the gauge shows the time",
+        conflicts_with_all = ["plain", "json_events", "stopwatch"]
+    )]
+    precision: Precision,
+
+    #[arg(
+        long,
+        help = "Show the time left in big block digits above the gauge, if the terminal has room",
+        conflicts_with_all = ["plain", "json_events", "stopwatch"]
+    )]
+    big: bool,
+
+    #[arg(
+        long,
+        help = "Start with the clock times the segment and the cycle end at shown under the \
+                gauge; a key toggles them either way",
+        conflicts_with_all = ["plain", "json_events", "stopwatch"]
+    )]
+    show_time_of_completion: bool,
+
+    #[arg(
+        long,
+        help = "Also remind you every this many minutes, e.g. to stand up, whatever the segments \
+                are doing and through pauses",
+        value_name = "MINUTES",
+        value_parser = parse_minutes,
+        conflicts_with = "stopwatch"
+    )]
+    reminder: Option<u64>,
+
+    #[arg(
+        long,
+        help = "What the reminder says",
+        value_name = "TEXT",
+        default_value = "Time to stand up",
+        requires = "reminder"
+    )]
+    reminder_text: String,
+
+    #[arg(
+        long,
+        help = "Ask before quitting during a work segment; Ctrl-C still quits straight away",
+        conflicts_with_all = ["plain", "json_events", "stopwatch"]
+    )]
+    strict: bool,
+
+    #[arg(
+        long,
+        help = "Wait for the pause key before starting each segment after the first, instead of \
+                going straight on",
+        conflicts_with_all = ["plain", "json_events", "stopwatch"]
+    )]
+    confirm_transitions: bool,
+
+    #[arg(
+        long,
+        help = "Pick up where the last session was quit, if it ran the same segments",
+        conflicts_with = "stopwatch"
+    )]
+    resume: bool,
+
+    #[arg(
+        long,
+        help = "Start paused, so the first segment or the stopwatch begins when you unpause it"
+    )]
+    start_paused: bool,
+
+    #[arg(
+        long,
+        help = "Pause the countdown while kit is suspended with Ctrl-Z, instead of letting it run \
+                on in the background",
+        conflicts_with = "stopwatch"
+    )]
+    pause_on_suspend: bool,
+
+    #[arg(
+        long,
+        help = "Pause a work segment once no key has been pressed in this terminal for this many \
+                seconds, and go on at the next key",
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with = "stopwatch"
+    )]
+    auto_pause: Option<u64>,
+
+    #[arg(
+        long,
+        help = "How often to check on the countdown, in milliseconds; the screen is only redrawn \
+                when what it shows changes",
+        value_name = "MS",
+        default_value_t = TICK_RATE.as_millis() as u64,
+        value_parser = clap::value_parser!(u64).range(10..=10_000),
+        conflicts_with = "stopwatch"
+    )]
+    refresh: u64,
+
+    #[arg(
+        long,
+        help = "Draw the TUI without colors (also set by the NO_COLOR variable)"
+    )]
+    no_color: bool,
+
+    #[arg(
+        long,
+        help = "Color the TUI with a built-in theme instead of the config file's: `default`, \
+                `solarized`, `mono` or `high-contrast`",
+        value_name = "NAME",
+        value_parser = theme::parse_preset
+    )]
+    theme: Option<Theme>,
+
+    #[arg(
+        long,
+        help = "Draw the TUI with plain ASCII (also set by a TERM of `dumb` or `vt*`)"
+    )]
+    ascii_only: bool,
+
+    #[arg(
+        long,
+        help = "Capture the mouse and show clickable pause, skip and quit buttons in the TUI"
+    )]
+    mouse: bool,
+
+    #[arg(
+        long,
+        help = "Dim the gauge and border colors a little in the evening and more overnight, going \
+                by the local time",
+        conflicts_with_all = ["plain", "json_events", "no_color"]
+    )]
+    auto_dim: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum PomoSubcommand {
+    #[command(name = "stats")]
+    Stats(StatsCommand),
+}
+
+impl PomoCommand {
+    /// Record the end of a segment in the `--csv-log` file, if there is one, and of a work
+    /// segment in the history log. Breaks are not in the history log.
+    fn log_segment(
+        &self,
+        segment: &PomoSegment,
+        elapsed: Duration,
+        paused: Duration,
+        outcome: Outcome,
+    ) -> std::io::Result<()> {
+        let record = HistoryRecord::new(
+            segment.kind(),
+            segment.task(),
+            segment.duration(),
+            elapsed,
+            paused,
+            outcome,
+        );
+        if let Some(path) = &self.csv_log {
+            history::append_csv(path, &record)?;
+        }
+        if !matches!(segment, PomoSegment::Work(..)) {
+            return Ok(());
+        }
+        match self.log.clone().or_else(history::default_path) {
+            Some(path) => history::append(&path, &record),
+            None => Ok(()),
+        }
+    }
+
+    /// A work segment for each task with a short break after it, except that every `n_pomos`th
+    /// and the last get a long break.
+    fn task_segments(&self, tasks: Vec<PomoSegment>, config: &PomoConfig) -> Vec<PomoSegment> {
+        let break_ = self.break_.or(config.break_).unwrap_or(DEFAULT_BREAK);
+        let long_break = self
+            .long_break
+            .or(config.long_break)
+            .unwrap_or(DEFAULT_LONG_BREAK);
+        let n_pomos = self.n_pomos.or(config.n_pomos).unwrap_or(DEFAULT_N_POMOS) as usize;
+        let n_tasks = tasks.len();
+        tasks
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, task)| {
+                let long = (i + 1) % n_pomos.max(1) == 0 || i + 1 == n_tasks;
+                let after = if long && !self.no_long_break {
+                    PomoSegment::LongBreak(long_break)
+                } else {
+                    PomoSegment::ShortBreak(break_)
+                };
+                [task, after]
+            })
+            .collect()
+    }
+
+    /// The segment lengths and keys from the flags, falling back to the config and then the
+    /// defaults, the same way `default_segments` picks them.
+    fn resolved_config(&self, config: &PomoConfig, keys: &Keymap) -> ResolvedPomoConfig {
+        ResolvedPomoConfig {
+            time: self.time.or(config.time).unwrap_or(DEFAULT_TIME),
+            break_: self.break_.or(config.break_).unwrap_or(DEFAULT_BREAK),
+            long_break: self
+                .long_break
+                .or(config.long_break)
+                .unwrap_or(DEFAULT_LONG_BREAK),
+            n_pomos: self.n_pomos.or(config.n_pomos).unwrap_or(DEFAULT_N_POMOS),
+            keys: keys.to_config(),
+        }
+    }
+
+    /// The `build_segments` cycle for the flags and config, or with `--no-long-break` just one
+    /// work segment and one short break.
+    fn default_segments(&self, config: &PomoConfig) -> Vec<PomoSegment> {
+        let time = self.time.or(config.time).unwrap_or(DEFAULT_TIME);
+        let break_ = self.break_.or(config.break_).unwrap_or(DEFAULT_BREAK);
+        if self.no_long_break {
+            return vec![
+                PomoSegment::Work(time, None),
+                PomoSegment::ShortBreak(break_),
+            ];
+        }
+        let long_break = self
+            .long_break
+            .or(config.long_break)
+            .unwrap_or(DEFAULT_LONG_BREAK);
+        let n_pomos = self.n_pomos.or(config.n_pomos).unwrap_or(DEFAULT_N_POMOS);
+        build_segments(time, break_, long_break, n_pomos)
+    }
+
+    fn display_options(&self, theme: Theme, keys: Keymap) -> DisplayOptions {
+        DisplayOptions {
+            big_digits: self.big,
+            precision: self.precision,
+            end_times: self.show_time_of_completion,
+            compact: self.quiet,
+            auto_dim: self.auto_dim,
+            ..DisplayOptions::new(
+                theme
+                    .overridden_by(self.theme)
+                    .with_ascii_only(self.ascii_only),
+                keys,
+                self.no_color,
+                self.mouse,
+            )
+        }
+    }
+
+    /// The segment index and time left to start from for `--resume`, or `None` to start from the
+    /// beginning because nothing was saved or it was saved by a session with other segments.
+    fn resume_point(
+        &self,
+        saved: Option<SavedState>,
+        segments_list: &[PomoSegment],
+    ) -> Option<(usize, Duration)> {
+        let saved = match saved {
+            Some(saved) => saved,
+            None => {
+                log::warn!("no quit session to resume, so starting from the beginning");
+                return None;
+            }
+        };
+        let segment = &segments_list[saved.segment_index % segments_list.len()];
+        let remaining = Duration::from_secs(saved.remaining_secs);
+        let cycles = self.repeat.map_or(usize::MAX, |cycles| cycles as usize);
+        let fits = saved.schedule == format_schedule(segments_list)
+            && !remaining.is_zero()
+            && remaining <= segment.duration()
+            && saved.segment_index / segments_list.len() < cycles;
+        if !fits {
+            log::warn!("the quit session ran different segments, so starting from the beginning");
+            return None;
+        }
+        log::info!(
+            "resuming segment {} ({}) with {} left, as of {}",
+            saved.segment_index + 1,
+            segment,
+            format_mmss(remaining),
+            saved.saved_at.format("%Y-%m-%d %H:%M")
+        );
+        Some((saved.segment_index, remaining))
+    }
+
+    /// Remember where a quit session stopped, or forget it once a session runs to the end.
+    fn save_state(&self, session: &Session, segments_list: &[PomoSegment]) -> std::io::Result<()> {
+        let path = match resume::default_path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        match (session.finished, session.quit_at) {
+            (true, _) => resume::clear(&path),
+            (false, Some((segment_index, remaining))) => resume::save(
+                &path,
+                &SavedState {
+                    saved_at: Local::now(),
+                    schedule: format_schedule(segments_list),
+                    segment_index,
+                    remaining_secs: remaining.as_secs(),
+                },
+            ),
+            (false, None) => Ok(()),
+        }
+    }
+
+    /// Load the sounds for `--sound`, `--work-sound` and `--break-sound` up front, so a bad file
+    /// is reported before the first segment rather than at the end of it.
+    fn alerts(&self) -> Result<Alerts, KitError> {
+        // Errors say which flag the file came from, since the same one can be behind several.
+        let load = |flag: &str, path: &Option<PathBuf>| {
+            let (flag, path) = match (path, &self.sound) {
+                (Some(path), _) => (flag, path),
+                (None, Some(path)) => ("--sound", path),
+                (None, None) => return Ok(None),
+            };
+            match Sound::load(path) {
+                Ok(sound) => Ok(Some(sound.with_playback(self.volume, self.alert_repeat))),
+                Err(KitError::Sound(e)) => Err(KitError::Sound(format!("{}: {}", flag, e))),
+                Err(e) => Err(e),
+            }
+        };
+        Ok(Alerts {
+            work: load("--work-sound", &self.work_sound)?,
+            break_: load("--break-sound", &self.break_sound)?,
+        })
+    }
+
+    fn tick_rate(&self) -> Duration {
+        Duration::from_millis(self.refresh)
+    }
+
+    /// Settings that won't stop a session from running but probably don't do what was meant,
+    /// each naming the flag or config key at fault. `now` is when `--until` counts from.
+    fn warnings(
+        &self,
+        config: &PomoConfig,
+        segments: &[PomoSegment],
+        now: NaiveDateTime,
+    ) -> Vec<String> {
+        let mut warnings = vec![];
+        if self.sequence.is_some() {
+            let flags = [
+                ("--time", self.time),
+                ("--break", self.break_),
+                ("--long-break", self.long_break),
+                ("--n-pomos", self.n_pomos),
+            ];
+            let ignored: Vec<&str> = flags
+                .iter()
+                .filter(|(_, value)| value.is_some())
+                .map(|(flag, _)| *flag)
+                .collect();
+            if !ignored.is_empty() {
+                warnings.push(format!(
+                    "ignoring {} in favor of --sequence",
+                    ignored.join(", ")
+                ));
+            }
+        }
+        if self.no_long_break {
+            if self.n_pomos.is_some() {
+                warnings.push("--n-pomos does nothing with --no-long-break".to_string());
+            }
+            if config.long_break.is_some() {
+                warnings.push(
+                    "`long_break` in the [pomo] section of the config file is unused with \
+                     --no-long-break"
+                        .to_string(),
+                );
+            }
+        }
+        if let Some(minutes) = segments
+            .iter()
+            .map(|s| s.duration().as_secs() / 60)
+            .max()
+            .filter(|&minutes| minutes > MAX_SEGMENT_MINUTES)
+        {
+            warnings.push(format!(
+                "a segment is {} minutes long, which is over a day",
+                minutes
+            ));
+        }
+        if self.repeat == Some(0) {
+            warnings.push("--repeat 0 runs no cycles, so no segment would ever start".to_string());
+        }
+        let before_long_break: Duration = segments
+            .iter()
+            .take_while(|s| !matches!(s, PomoSegment::LongBreak(_)))
+            .map(PomoSegment::duration)
+            .fold(Duration::ZERO, Duration::saturating_add);
+        let has_long_break = segments
+            .iter()
+            .any(|s| matches!(s, PomoSegment::LongBreak(_)));
+        if let Some(until) = self.until.filter(|_| has_long_break) {
+            if until_deadline(now, until) <= before_long_break {
+                warnings.push(format!(
+                    "--until {} comes before the first long break, {} into the cycle, so it's \
+                     never reached",
+                    until.format("%H:%M"),
+                    format_minutes(before_long_break.as_secs() / 60)
+                ));
+            }
+        }
+        warnings
+    }
+
+    /// How many cycles to run before finishing, which `--quiet` makes one unless told otherwise.
+    fn cycles(&self) -> Option<u64> {
+        self.repeat.or(self.quiet.then_some(1))
+    }
+
+    fn emit(&self, event: PomoEvent) -> std::io::Result<()> {
+        if !self.json_events {
+            return Ok(());
+        }
+        event.write(&mut stdout().lock())
+    }
+
+    pub async fn run(&self) -> Result<(), KitError> {
+        if let Some(PomoSubcommand::Stats(stats)) = &self.subcommand {
+            return stats.run().await;
+        }
+        let config = Config::load()?;
+        let options = self.display_options(config.theme, load_keys(&config)?);
+        if self.stopwatch {
+            let mut stopwatch = Stopwatch::new(options, self.start_paused);
+            run_app(&mut stopwatch).await?;
+            for (i, lap) in stopwatch.laps.iter().enumerate() {
+                println!("Lap {}: {}", i + 1, format_hms(*lap));
+            }
+            println!("Total elapsed: {}", format_hms(stopwatch.elapsed()));
+            return Ok(());
+        }
+        let segments_list = match (&self.sequence, &self.tasks) {
+            (Some(spec), _) => parse_sequence(spec).map_err(|e| {
+                KitError::InvalidArgument(format!("invalid --sequence {:?}: {}", spec, e))
+            })?,
+            (None, Some(path)) => self.task_segments(read_tasks(path)?, &config.pomo),
+            (None, None) if self.quiet => vec![PomoSegment::Work(
+                self.time.or(config.pomo.time).unwrap_or(DEFAULT_TIME),
+                None,
+            )],
+            (None, None) => self.default_segments(&config.pomo),
+        };
+        check_segments(&segments_list).map_err(KitError::Config)?;
+        let warnings = self.warnings(&config.pomo, &segments_list, Local::now().naive_local());
+        if self.check {
+            // Sounds are only loaded once a session starts, so they'd go unchecked otherwise.
+            self.alerts()?;
+            for warning in &warnings {
+                println!("warning: {}", warning);
+            }
+            println!("{}", check_summary(warnings.len()));
+            return Ok(());
+        }
+        for warning in &warnings {
+            log::warn!("{}", warning);
+        }
+        if self.show_schedule {
+            println!("{}", format_schedule(&segments_list));
+            return Ok(());
+        }
+        if self.print_config {
+            let resolved = self.resolved_config(&config.pomo, &options.keys);
+            let custom = self.sequence.is_some() || self.tasks.is_some() || self.no_long_break;
+            let toml = format_config(&resolved, &segments_list, custom)
+                .map_err(|e| KitError::Config(e.to_string()))?;
+            print!("{}", toml);
+            return Ok(());
+        }
+        if self.cycles() == Some(0) {
+            return Ok(());
+        }
+        let alerts = self.alerts()?;
+        let start = if self.resume {
+            let saved = resume::default_path().and_then(|path| resume::load(&path));
+            self.resume_point(saved, &segments_list)
+        } else {
+            None
+        };
+        let plain = self.plain || self.json_events || !with_tui::is_terminal();
+        if plain && !self.plain && !self.json_events {
+            log::info!("not running in a terminal, so falling back to --plain");
+        }
+        let session = if plain {
+            self.run_plain(&segments_list, start, &alerts, &options.keys)
+                .await?
+        } else {
+            let mut app = CountdownApp {
+                runner: SegmentRunner::new(self, &segments_list, start, &alerts)?,
+                options,
+                buttons: Buttons::default(),
+                flash_started: None,
+                drawn: None,
+                confirming_quit: false,
+                paused_by_suspend: false,
+            };
+            run_app(&mut app).await?;
+            app.runner.session
+        };
+        if let Err(e) = self.save_state(&session, &segments_list) {
+            log::warn!("cannot save where the session stopped: {}", e);
+        }
+        match (session.finished, self.cycles()) {
+            (true, _) if self.quiet => println!("{}", session.done()),
+            (true, Some(cycles)) => println!("{}", session.summary(cycles)),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Run the segments with a single status line on stdout instead of the TUI, or with nothing
+    /// but the `--json-events` output. Keys are only read when stdin is a terminal, and the line is
+    /// only redrawn in place when stdout is one; otherwise each segment gets one line as it starts.
+    async fn run_plain(
+        &self,
+        segments_list: &[PomoSegment],
+        start: Option<(usize, Duration)>,
+        alerts: &Alerts,
+        keys: &Keymap,
+    ) -> Result<Session, KitError> {
+        let status_line = !self.json_events;
+        let animate = status_line && stdout().is_terminal();
+        let (raw_mode, mut events) = if stdin().is_terminal() {
+            (Some(RawModeGuard::enable()?), EventStream::new().boxed())
+        } else {
+            (None, futures::stream::pending().boxed())
+        };
+        let mut runner = SegmentRunner::new(self, segments_list, start, alerts)?;
+        let mut ticks = time::interval(self.tick_rate());
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut last_printed = None;
+        let plain_help = format!(
+            "  ({}: pause, {}: skip, {}: back, {}: quit)",
+            keys.describe(PomoInput::Pause),
+            keys.describe(PomoInput::Skip),
+            keys.describe(PomoInput::Previous),
+            keys.describe(PomoInput::Quit)
+        );
+        let interrupted = tokio::signal::ctrl_c();
+        tokio::pin!(interrupted);
+        let result: Result<(), KitError> = async {
+            loop {
+                if status_line {
+                    let segment = runner.segment();
+                    let remaining = runner.remaining();
+                    let mut stdout = stdout();
+                    if animate {
+                        let hint = if runner.show_help { &plain_help } else { "" };
+                        write!(
+                            stdout,
+                            "\r{}{}",
+                            format_plain(segment, remaining, runner.is_paused),
+                            hint
+                        )?;
+                        stdout.execute(Clear(ClearType::UntilNewLine))?;
+                    } else if last_printed != Some(runner.i) {
+                        let line = format_plain(segment, remaining, runner.is_paused);
+                        writeln!(stdout, "{}", line)?;
+                        last_printed = Some(runner.i);
+                    }
+                    stdout.flush()?;
+                }
+                let flow = tokio::select! {
+                    _ = ticks.tick() => runner.tick()?,
+                    signal = &mut interrupted => {
+                        signal?;
+                        log::info!("interrupted");
+                        runner.handle_input(PomoInput::Quit)?
+                    }
+                    maybe_event = events.next() => match maybe_event {
+                        Some(Ok(event)) => {
+                            let input = keys.input(event);
+                            match input {
+                                _ if matches!(event, Event::Key(_))
+                                    && runner.key_pressed(input)? =>
+                                {
+                                    ControlFlow::Continue(())
+                                }
+                                Some(input) => runner.handle_input(input)?,
+                                None => ControlFlow::Continue(()),
+                            }
+                        }
+                        Some(Err(e)) => return Err(e.into()),
+                        None => ControlFlow::Break(()),
+                    },
+                };
+                if flow.is_break() {
+                    return Ok(());
+                }
+            }
+        }
+        .await;
+        drop(raw_mode);
+        if animate {
+            println!();
+        }
+        result.map(|()| runner.session)
+    }
+}
+
+/// One cycle of `n_pomos` work segments separated by short breaks and followed by a long break.
+/// A cycle always has at least one work segment, so `n_pomos` of 0 counts as 1.
+fn build_segments(time: u64, break_: u64, long_break: u64, n_pomos: u64) -> Vec<PomoSegment> {
+    Itertools::intersperse(
+        repeat_n(PomoSegment::Work(time, None), n_pomos.max(1) as usize),
+        PomoSegment::ShortBreak(break_),
+    )
+    .chain(once(PomoSegment::LongBreak(long_break)))
+    .collect()
+}
+
+/// A length in minutes for a flag, as a bare number or a duration like `1h30m`.
+fn parse_minutes(s: &str) -> Result<u64, String> {
+    match duration::parse_minutes(s) {
+        Ok(0) => Err("a segment must be at least 1 minute long".to_string()),
+        result => result.map_err(|e| e.to_string()),
+    }
+}
+
+/// Reject zero-length segments, which can still come from the config file. Ones longer than a
+/// day are only warned about, by `PomoCommand::warnings`.
+fn check_segments(segments: &[PomoSegment]) -> Result<(), String> {
+    if let Some(segment) = segments.iter().find(|s| s.duration().is_zero()) {
+        let key = match segment {
+            PomoSegment::Work(..) => "time",
+            PomoSegment::ShortBreak(_) => "break",
+            PomoSegment::LongBreak(_) => "long_break",
+        };
+        return Err(format!(
+            "a {} of 0 minutes can't run; check `{}` in the [pomo] section of the config file",
+            segment.kind().to_lowercase(),
+            key
+        ));
+    }
+    Ok(())
+}
+
+/// The last line `--check` prints.
+fn check_summary(n_warnings: usize) -> String {
+    match n_warnings {
+        0 => "Config and flags look good".to_string(),
+        1 => "Config and flags are usable, with 1 warning".to_string(),
+        n => format!("Config and flags are usable, with {} warnings", n),
+    }
+}
+
+/// How often running timers are checked on and redrawn
+const TICK_RATE: Duration = Duration::from_millis(100);
+
+fn load_keys(config: &Config) -> Result<Keymap, KitError> {
+    Keymap::new(&config.pomo.keys)
+        .map_err(|e| KitError::Config(format!("invalid [pomo.keys] in the config file: {}", e)))
+}
+
+/// `duration`, or however much of it fits before `deadline`.
+fn fit_before(duration: Duration, deadline: Option<Instant>) -> Duration {
+    match deadline {
+        Some(deadline) => duration.min(deadline.saturating_duration_since(Instant::now())),
+        None => duration,
+    }
+}
+
+/// How long from `now` until the next time the clock reads `time`, which is tomorrow if that time
+/// has already passed today.
+fn until_deadline(now: NaiveDateTime, time: NaiveTime) -> Duration {
+    let today = now.date().and_time(time);
+    let deadline = if today > now {
+        today
+    } else {
+        today + chrono::Duration::days(1)
+    };
+    (deadline - now).to_std().unwrap_or_default()
+}
+
+/// The next moment the local clock reads `time`, for `--until` and `kit alarm`.
+fn next_deadline(time: NaiveTime) -> Instant {
+    Instant::now() + until_deadline(Local::now().naive_local(), time)
+}
+
+fn parse_clock_time(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, "%H:%M")
+        .map_err(|_| format!("expected a time like 17:00, got {:?}", s))
+}
+
+/// A `countdown` running in the background, with the channels to follow and steer it.
+struct Countdown {
+    duration: Duration,
+    rx_state: watch::Receiver<CountdownState>,
+    tx_paused: watch::Sender<bool>,
+    tx_cancel: mpsc::Sender<()>,
+    handle: JoinHandle<Result<(), KitError>>,
+}
+
+impl Countdown {
+    /// Spawn the countdown, already paused if `paused` is set, updating its state every
+    /// `tick_rate`.
+    fn start(duration: Duration, paused: bool, tick_rate: Duration) -> Self {
+        let (tx_state, rx_state) = watch::channel(CountdownState::new(duration));
+        let (tx_paused, rx_paused) = watch::channel(paused);
+        let (tx_cancel, rx_cancel) = mpsc::channel(1);
+        let handle = tokio::spawn(countdown(
+            duration, tick_rate, tx_state, rx_paused, rx_cancel,
+        ));
+        Self {
+            duration,
+            rx_state,
+            tx_paused,
+            tx_cancel,
+            handle,
+        }
+    }
+
+    fn state(&self) -> CountdownState {
+        *self.rx_state.borrow()
+    }
+
+    fn remaining(&self) -> Duration {
+        self.state().remaining
+    }
+}
+
+/// What `countdown` reports as it goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct CountdownState {
+    remaining: Duration,
+    /// Time spent paused, not counting a pause still going on
+    paused: Duration,
+    /// When the pause going on began, if there is one
+    paused_since: Option<Instant>,
+}
+
+impl CountdownState {
+    fn new(remaining: Duration) -> Self {
+        Self {
+            remaining,
+            paused: Duration::ZERO,
+            paused_since: None,
+        }
+    }
+
+    /// Time spent paused so far, up to now if the countdown is paused.
+    fn total_paused(&self) -> Duration {
+        self.paused
+            + self
+                .paused_since
+                .map_or(Duration::ZERO, |since| since.elapsed())
+    }
+}
+
+/// Cycles through the segments until the user quits or the `--repeat` or `--until` limit is
+/// reached. The TUI and `--plain` both drive it, calling `tick` regularly and passing on the
+/// user's inputs, and either can end the session.
+struct SegmentRunner<'a> {
+    command: &'a PomoCommand,
+    segments_list: &'a [PomoSegment],
+    alerts: &'a Alerts,
+    n_segments: usize,
+    /// When to stop for `--until`. Segments are cut short so as not to run past it, although
+    /// pausing still pushes the end back.
+    deadline: Option<Instant>,
+    /// Index of the current segment, counting from the start of the session
+    i: usize,
+    countdown: Countdown,
+    is_paused: bool,
+    /// Whether `--confirm-transitions` is holding the segment at its full length until it's
+    /// started. The countdown is paused meanwhile, but the wait isn't logged as a pause.
+    waiting: bool,
+    /// When a key was last pressed, for `--auto-pause`
+    last_key: Instant,
+    /// Whether `--auto-pause` paused the countdown, so that the next key resumes it
+    auto_paused: bool,
+    show_help: bool,
+    /// Whole seconds remaining as of the last tick event
+    last_tick_secs: u64,
+    session: Session,
+    reminder: Option<Reminder>,
+}
+
+/// How long the reminder's text stays up once it goes off
+const REMINDER_SHOWN: Duration = Duration::from_secs(10);
+
+/// The `--reminder` countdown, which starts over every time it runs out. It runs alongside the
+/// segments without ever waiting for them.
+struct Reminder {
+    every: Duration,
+    countdown: Countdown,
+    went_off: Option<Instant>,
+}
+
+impl<'a> SegmentRunner<'a> {
+    /// Start the first segment, or with `start`, the segment at that index with that much left.
+    fn new(
+        command: &'a PomoCommand,
+        segments_list: &'a [PomoSegment],
+        start: Option<(usize, Duration)>,
+        alerts: &'a Alerts,
+    ) -> std::io::Result<Self> {
+        let deadline = command.until.map(|time| {
+            let deadline = next_deadline(time);
+            log::info!(
+                "stopping at {} (in {})",
+                time,
+                format_hms(deadline - Instant::now())
+            );
+            deadline
+        });
+        let (i, duration) = start.unwrap_or((0, segments_list[0].duration()));
+        let duration = fit_before(duration, deadline);
+        let runner = Self {
+            command,
+            segments_list,
+            alerts,
+            n_segments: command
+                .cycles()
+                .map_or(usize::MAX, |cycles| cycles as usize * segments_list.len()),
+            deadline,
+            i,
+            countdown: Countdown::start(duration, command.start_paused, command.tick_rate()),
+            is_paused: command.start_paused,
+            waiting: false,
+            last_key: Instant::now(),
+            auto_paused: false,
+            show_help: false,
+            last_tick_secs: duration.as_secs(),
+            session: Session {
+                outcomes: vec![None; i],
+                ..Session::default()
+            },
+            reminder: command.reminder.map(|minutes| {
+                let every = Duration::from_secs(minutes.saturating_mul(60));
+                Reminder {
+                    every,
+                    countdown: Countdown::start(every, false, command.tick_rate()),
+                    went_off: None,
+                }
+            }),
+        };
+        runner.announce()?;
+        Ok(runner)
+    }
+
+    fn segment(&self) -> &'a PomoSegment {
+        &self.segments_list[self.i % self.segments_list.len()]
+    }
+
+    fn remaining(&self) -> Duration {
+        self.countdown.remaining()
+    }
+
+    fn announce(&self) -> std::io::Result<()> {
+        let segment = self.segment();
+        let duration = self.countdown.duration;
+        log::info!(
+            "starting segment {}: {} ({:?})",
+            self.i + 1,
+            segment,
+            duration
+        );
+        self.command.emit(PomoEvent::Start {
+            segment: segment.kind(),
+            duration_secs: duration.as_secs(),
+        })
+    }
+
+    /// Start the next segment, or finish the session if the current one was the last or the
+    /// deadline has come.
+    fn advance(&mut self) -> Result<ControlFlow<()>, KitError> {
+        self.i += 1;
+        if self.i >= self.n_segments {
+            log::debug!("all {} segments completed", self.n_segments);
+            self.session.finished = true;
+            return Ok(ControlFlow::Break(()));
+        }
+        // Anything under a second is the countdown finishing a moment after the deadline it was
+        // measured against, not time worth starting another segment for.
+        let left = self
+            .deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        if left.is_some_and(|left| left < Duration::from_secs(1)) {
+            log::debug!("reached the --until deadline");
+            self.session.finished = true;
+            return Ok(ControlFlow::Break(()));
+        }
+        if self.command.confirm_transitions {
+            self.wait();
+        } else {
+            self.restart()?;
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    /// Hold the current segment at its full length until the pause key starts it.
+    fn wait(&mut self) {
+        let duration = fit_before(self.segment().duration(), self.deadline);
+        log::info!(
+            "waiting to start segment {}: {}",
+            self.i + 1,
+            self.segment()
+        );
+        self.countdown = Countdown::start(duration, true, self.command.tick_rate());
+        self.is_paused = true;
+        self.auto_paused = false;
+        self.waiting = true;
+        self.last_tick_secs = duration.as_secs();
+    }
+
+    /// Go back to the segment before this one, or to the start of this one if it's the first,
+    /// and take its mark off the segment list since it's being done again.
+    fn go_back(&mut self) -> std::io::Result<()> {
+        self.i = self.i.saturating_sub(1);
+        self.session.outcomes.truncate(self.i);
+        self.restart()
+    }
+
+    /// Start the countdown for the current segment from the top.
+    fn restart(&mut self) -> std::io::Result<()> {
+        let duration = fit_before(self.segment().duration(), self.deadline);
+        self.countdown = Countdown::start(duration, false, self.command.tick_rate());
+        self.is_paused = false;
+        self.auto_paused = false;
+        self.waiting = false;
+        self.last_tick_secs = duration.as_secs();
+        self.announce()
+    }
+
+    /// Signal the reminder and start its countdown over if it has run out.
+    fn check_reminder(&mut self) -> Result<(), KitError> {
+        let Some(reminder) = &mut self.reminder else {
+            return Ok(());
+        };
+        let Some(finished) = (&mut reminder.countdown.handle).now_or_never() else {
+            return Ok(());
+        };
+        finished??;
+        log::info!("reminder: {}", self.command.reminder_text);
+        reminder.went_off = Some(Instant::now());
+        reminder.countdown = Countdown::start(reminder.every, false, self.command.tick_rate());
+        self.command.emit(PomoEvent::Reminder)?;
+        if self.command.bell {
+            sound::bell()?;
+        }
+        if self.command.notify {
+            let shown = Notification::new()
+                .summary(&self.command.reminder_text)
+                .show();
+            if let Err(e) = shown {
+                log::debug!("failed to show notification: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// The reminder's text for a while after it goes off, and otherwise how long until it does.
+    fn reminder_text(&self) -> Option<String> {
+        let reminder = self.reminder.as_ref()?;
+        match reminder.went_off {
+            Some(went_off) if went_off.elapsed() < REMINDER_SHOWN => {
+                Some(format!("{}!", self.command.reminder_text))
+            }
+            _ => Some(format!(
+                "Reminder in: {}",
+                format_mmss(reminder.countdown.remaining())
+            )),
+        }
+    }
+
+    fn pause_state(&self) -> PauseState {
+        match (self.is_paused, self.auto_paused) {
+            (false, _) => PauseState::Running,
+            (true, false) => PauseState::Paused,
+            (true, true) => PauseState::AutoPaused,
+        }
+    }
+
+    /// Note a key press for `--auto-pause`, resuming if it had paused. Returns whether the key
+    /// was spent on that, as any key but quit is.
+    fn key_pressed(&mut self, input: Option<PomoInput>) -> Result<bool, KitError> {
+        self.last_key = Instant::now();
+        if !self.auto_paused {
+            return Ok(false);
+        }
+        let _ = self.handle_input(PomoInput::Pause)?;
+        Ok(input != Some(PomoInput::Quit))
+    }
+
+    /// Pause a running work segment once `--auto-pause` has gone by without a key.
+    fn check_idle(&mut self) -> Result<(), KitError> {
+        let Some(idle) = self.command.auto_pause else {
+            return Ok(());
+        };
+        let is_work = matches!(self.segment(), PomoSegment::Work(..));
+        if is_work && !self.is_paused && self.last_key.elapsed() >= Duration::from_secs(idle) {
+            log::info!("pausing after {}s without a key", idle);
+            let _ = self.handle_input(PomoInput::Pause)?;
+            self.auto_paused = true;
+        }
+        Ok(())
+    }
+
+    /// Move on once the countdown is over, and otherwise emit a tick event for each second gone by.
+    fn tick(&mut self) -> Result<ControlFlow<()>, KitError> {
+        self.check_reminder()?;
+        self.check_idle()?;
+        let segment = self.segment();
+        let name = segment.kind();
+        if let Some(finished) = (&mut self.countdown.handle).now_or_never() {
+            finished??;
+            log::info!("finished segment {}: {}", self.i + 1, segment);
+            self.command.emit(PomoEvent::Complete { segment: name })?;
+            self.command.log_segment(
+                segment,
+                self.countdown.duration,
+                self.countdown.state().total_paused(),
+                Outcome::Completed,
+            )?;
+            self.session.completed.push(segment.clone());
+            self.session.outcomes.push(Some(Outcome::Completed));
+            if let Some(sound) = self.alerts.for_segment(segment) {
+                sound.spawn_play();
+            }
+            if self.command.bell {
+                sound::bell()?;
+            }
+            if self.command.notify {
+                let next = &self.segments_list[(self.i + 1) % self.segments_list.len()];
+                if let Err(e) = notify_transition(segment, next) {
+                    log::debug!("failed to show notification: {}", e);
+                }
+            }
+            return self.advance();
+        }
+        let remaining_secs = self.remaining().as_secs();
+        if remaining_secs != self.last_tick_secs && !self.is_paused {
+            self.command.emit(PomoEvent::Tick {
+                segment: name,
+                remaining_secs,
+            })?;
+            self.last_tick_secs = remaining_secs;
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    fn handle_input(&mut self, input: PomoInput) -> Result<ControlFlow<()>, KitError> {
+        let segment = self.segment();
+        let name = segment.kind();
+        let state = self.countdown.state();
+        let remaining = state.remaining;
+        let remaining_secs = remaining.as_secs();
+        match input {
+            PomoInput::Help => self.show_help = !self.show_help,
+            // Starting afresh rather than unpausing keeps the wait out of the time paused.
+            PomoInput::Pause if self.waiting => {
+                self.countdown.tx_cancel.try_send(())?;
+                self.restart()?;
+            }
+            PomoInput::Pause => {
+                self.is_paused = !self.is_paused;
+                self.auto_paused = false;
+                self.countdown.tx_paused.send(self.is_paused)?;
+                self.command.emit(if self.is_paused {
+                    PomoEvent::Pause {
+                        segment: name,
+                        remaining_secs,
+                    }
+                } else {
+                    PomoEvent::Resume {
+                        segment: name,
+                        remaining_secs,
+                    }
+                })?;
+            }
+            PomoInput::Skip => {
+                log::info!("skipped segment {}: {}", self.i + 1, segment);
+                self.command.emit(PomoEvent::Skip {
+                    segment: name,
+                    remaining_secs,
+                })?;
+                self.countdown.tx_cancel.try_send(())?;
+                let elapsed = self.countdown.duration - remaining;
+                self.command.log_segment(
+                    segment,
+                    elapsed,
+                    state.total_paused(),
+                    Outcome::Skipped,
+                )?;
+                self.session.outcomes.push(Some(Outcome::Skipped));
+                return self.advance();
+            }
+            PomoInput::Previous => {
+                log::info!("went back from segment {}: {}", self.i + 1, segment);
+                self.command.emit(PomoEvent::Previous {
+                    segment: name,
+                    remaining_secs,
+                })?;
+                self.countdown.tx_cancel.try_send(())?;
+                // Cut short like a skip, so the history gets the time put in so far.
+                let elapsed = self.countdown.duration - remaining;
+                self.command.log_segment(
+                    segment,
+                    elapsed,
+                    state.total_paused(),
+                    Outcome::Skipped,
+                )?;
+                self.go_back()?;
+            }
+            PomoInput::Quit => {
+                log::info!("quit during segment {}: {}", self.i + 1, segment);
+                self.command.emit(PomoEvent::Quit {
+                    segment: name,
+                    remaining_secs,
+                })?;
+                // A segment that was never started has nothing to log.
+                if !self.waiting {
+                    let elapsed = self.countdown.duration - remaining;
+                    self.command.log_segment(
+                        segment,
+                        elapsed,
+                        state.total_paused(),
+                        Outcome::Quit,
+                    )?;
+                }
+                self.session.quit_at = Some((self.i, remaining));
+                return Ok(ControlFlow::Break(()));
+            }
+            PomoInput::Label | PomoInput::EndTimes | PomoInput::Resize => {}
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+}
+
+/// The countdown TUI, showing the current segment of a `SegmentRunner`.
+struct CountdownApp<'a> {
+    runner: SegmentRunner<'a>,
+    options: DisplayOptions,
+    /// Where the buttons went in the last draw, for matching clicks against
+    buttons: Buttons,
+    /// When the last segment to finish did so, if `--flash` is on
+    flash_started: Option<Instant>,
+    /// What the last draw showed, so that ticks which change none of it don't redraw
+    drawn: Option<Shown>,
+    /// Whether `--strict` is asking if the user really wants to quit
+    confirming_quit: bool,
+    /// Whether `--pause-on-suspend` paused the countdown, so that it should go on once resumed
+    paused_by_suspend: bool,
+}
+
+/// The parts of the countdown screen that change without any input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Shown {
+    i_segment: usize,
+    /// Time remaining in steps of the precision shown, or of seconds if that's finer
+    remaining_steps: u128,
+    paused_secs: u64,
+    pause: PauseState,
+    flash_frame: Option<u128>,
+    reminder: Option<String>,
+}
+
+/// How long `--flash` lasts, alternating between the flash and the usual screen every frame
+const FLASH_DURATION: Duration = Duration::from_millis(500);
+const FLASH_FRAME: Duration = Duration::from_millis(100);
+
+impl WithTui for CountdownApp<'_> {}
+
+impl CountdownApp<'_> {
+    fn flash_frame(&self) -> Option<u128> {
+        self.flash_started
+            .map(|started| started.elapsed())
+            .filter(|&elapsed| elapsed < FLASH_DURATION)
+            .map(|elapsed| elapsed.as_millis() / FLASH_FRAME.as_millis())
+    }
+
+    fn shown(&self) -> Shown {
+        let state = self.runner.countdown.state();
+        Shown {
+            i_segment: self.runner.i,
+            remaining_steps: state.remaining.as_millis()
+                / self.options.precision.redraw_step().as_millis(),
+            paused_secs: state.total_paused().as_secs(),
+            pause: self.runner.pause_state(),
+            flash_frame: self.flash_frame(),
+            reminder: self.runner.reminder_text(),
+        }
+    }
+}
+
+impl App for CountdownApp<'_> {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>) -> Result<(), KitError> {
+        self.drawn = Some(self.shown());
+        let runner = &self.runner;
+        let state = runner.countdown.state();
+        let flash_frame = self.flash_frame();
+        if let (Some(frame), Some(finished)) = (flash_frame, runner.session.completed.last()) {
+            if frame % 2 == 0 {
+                render_flash(f, &format!("{} done", finished));
+                return Ok(());
+            }
+        }
+        let progress = SessionProgress {
+            segments_list: runner.segments_list,
+            i_segment: runner.i,
+            outcomes: &runner.session.outcomes,
+        };
+        self.buttons = render_countdown(
+            f,
+            progress,
+            state,
+            runner.reminder_text().as_deref(),
+            runner.pause_state(),
+            runner.show_help,
+            &self.options,
+        );
+        if self.confirming_quit {
+            render_popup(f, &self.options.theme, "Really quit? y/n");
+        } else if runner.waiting {
+            let prompt = format!(
+                "Press {} to start {}",
+                self.options.keys.describe(PomoInput::Pause),
+                runner.segment()
+            );
+            render_popup(f, &self.options.theme, &prompt);
+        }
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<ControlFlow<()>, KitError> {
+        let input = PomoInput::from_event(event, &self.options.keys, &self.buttons);
+        if matches!(event, Event::Key(_)) && self.runner.key_pressed(input.ok())? {
+            return Ok(ControlFlow::Continue(()));
+        }
+        if self.confirming_quit {
+            // Only a key or a click answers; anything else, like a resize, leaves the question up.
+            if !matches!(event, Event::Key(_) | Event::Mouse(_)) {
+                return Ok(ControlFlow::Continue(()));
+            }
+            self.confirming_quit = false;
+            return match event {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('y' | 'Y'),
+                    ..
+                }) => self.runner.handle_input(PomoInput::Quit),
+                _ if is_ctrl_c(event) => self.runner.handle_input(PomoInput::Quit),
+                _ => Ok(ControlFlow::Continue(())),
+            };
+        }
+        match input {
+            Ok(PomoInput::Quit)
+                if self.runner.command.strict
+                    && matches!(self.runner.segment(), PomoSegment::Work(..))
+                    && !is_ctrl_c(event) =>
+            {
+                self.confirming_quit = true;
+                Ok(ControlFlow::Continue(()))
+            }
+            Ok(PomoInput::Label) => {
+                self.options.gauge_label = self.options.gauge_label.next();
+                Ok(ControlFlow::Continue(()))
+            }
+            Ok(PomoInput::EndTimes) => {
+                self.options.end_times = !self.options.end_times;
+                Ok(ControlFlow::Continue(()))
+            }
+            Ok(input) => self.runner.handle_input(input),
+            Err(()) => Ok(ControlFlow::Continue(())),
+        }
+    }
+
+    fn tick(&mut self) -> Result<ControlFlow<()>, KitError> {
+        let completed = self.runner.session.completed.len();
+        let flow = self.runner.tick()?;
+        if self.runner.command.flash && self.runner.session.completed.len() > completed {
+            self.flash_started = Some(Instant::now());
+        }
+        Ok(flow)
+    }
+
+    /// Quit as if from the keyboard, so the segment still makes it into the history.
+    fn interrupt(&mut self) -> Result<ControlFlow<()>, KitError> {
+        self.runner.handle_input(PomoInput::Quit)
+    }
+
+    /// Pausing goes through the usual input, so that it's logged and emitted like any other.
+    fn suspend(&mut self) -> Result<(), KitError> {
+        if self.runner.command.pause_on_suspend && !self.runner.is_paused {
+            let _ = self.runner.handle_input(PomoInput::Pause)?;
+            self.paused_by_suspend = true;
+        }
+        Ok(())
+    }
+
+    fn resume(&mut self) -> Result<(), KitError> {
+        if std::mem::take(&mut self.paused_by_suspend) && self.runner.is_paused {
+            let _ = self.runner.handle_input(PomoInput::Pause)?;
+        }
+        Ok(())
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.drawn != Some(self.shown())
+    }
+
+    fn tick_rate(&self) -> Option<Duration> {
+        Some(self.runner.command.tick_rate())
+    }
+
+    fn captures_mouse(&self) -> bool {
+        self.options.buttons
+    }
+
+    fn ascii_only(&self) -> bool {
+        self.options.theme.ascii_only
+    }
+
+    fn title(&self) -> Option<String> {
+        Some(format!(
+            "{} - {} - {}",
+            std::env::args().join(" "),
+            self.runner.segment(),
+            format_mmss(self.runner.remaining())
+        ))
+    }
+}
+
+/// Counts up until the user quits, recording laps along the way. This drives the same `countdown`
+/// as a segment would, just from a start so far off that it never reaches zero.
+struct Stopwatch {
+    countdown: Countdown,
+    is_paused: bool,
+    show_help: bool,
+    laps: Vec<Duration>,
+    last_lap_at: Duration,
+    options: DisplayOptions,
+    buttons: Buttons,
+}
+
+impl Stopwatch {
+    fn new(options: DisplayOptions, paused: bool) -> Self {
+        Self {
+            countdown: Countdown::start(Duration::MAX, paused, TICK_RATE),
+            is_paused: paused,
+            show_help: false,
+            laps: vec![],
+            last_lap_at: Duration::ZERO,
+            options,
+            buttons: Buttons::default(),
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        Duration::MAX - self.countdown.remaining()
+    }
+}
+
+impl WithTui for Stopwatch {}
+
+impl App for Stopwatch {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>) -> Result<(), KitError> {
+        let elapsed = self.elapsed();
+        self.buttons = render_stopwatch(
+            f,
+            elapsed,
+            &self.laps,
+            self.is_paused,
+            self.show_help,
+            &self.options,
+        );
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<ControlFlow<()>, KitError> {
+        match PomoInput::from_event(event, &self.options.keys, &self.buttons) {
+            Ok(PomoInput::Help) => self.show_help = !self.show_help,
+            Ok(PomoInput::Pause) => {
+                self.is_paused = !self.is_paused;
+                self.countdown.tx_paused.send(self.is_paused)?;
+            }
+            Ok(PomoInput::Skip) => {
+                let elapsed = self.elapsed();
+                self.laps.push(elapsed - self.last_lap_at);
+                self.last_lap_at = elapsed;
+            }
+            Ok(PomoInput::Quit) => {
+                self.countdown.tx_cancel.try_send(())?;
+                return Ok(ControlFlow::Break(()));
+            }
+            Ok(
+                PomoInput::Label | PomoInput::EndTimes | PomoInput::Resize | PomoInput::Previous,
+            )
+            | Err(()) => {}
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    fn tick_rate(&self) -> Option<Duration> {
+        Some(TICK_RATE)
+    }
+
+    fn captures_mouse(&self) -> bool {
+        self.options.buttons
+    }
+
+    fn ascii_only(&self) -> bool {
+        self.options.theme.ascii_only
+    }
+
+    fn title(&self) -> Option<String> {
+        Some(format!(
+            "{} - {}",
+            std::env::args().join(" "),
+            format_hms(self.elapsed())
+        ))
+    }
+}
+
+/// The sounds to play when work segments and breaks finish
+#[derive(Debug, Default)]
+struct Alerts {
+    work: Option<Sound>,
+    break_: Option<Sound>,
+}
+
+impl Alerts {
+    fn for_segment(&self, segment: &PomoSegment) -> Option<&Sound> {
+        match segment {
+            PomoSegment::Work(..) => self.work.as_ref(),
+            PomoSegment::ShortBreak(_) | PomoSegment::LongBreak(_) => self.break_.as_ref(),
+        }
+    }
+}
+
+/// The segments that ran to completion during a session, and whether the session ended by itself
+/// rather than by the user quitting.
+#[derive(Debug, Default)]
+struct Session {
+    completed: Vec<PomoSegment>,
+    /// How each segment ended, by index. Segments before the one a `--resume` started at are
+    /// `None`, as is the one quit during.
+    outcomes: Vec<Option<Outcome>>,
+    finished: bool,
+    /// The index of the segment the user quit during and how much of it was left
+    quit_at: Option<(usize, Duration)>,
+}
+
+impl Session {
+    fn work_minutes(&self) -> u64 {
+        self.completed
+            .iter()
+            .filter(|s| matches!(s, PomoSegment::Work(..)))
+            .map(|s| s.duration().as_secs() / 60)
+            .fold(0, u64::saturating_add)
+    }
+
+    fn summary(&self, cycles: u64) -> String {
+        let count = |f: fn(&PomoSegment) -> bool| self.completed.iter().filter(|s| f(s)).count();
+        let work_minutes = self.work_minutes();
+        format!(
+            "Completed {} cycle{}: {} work segments ({} min), {} short breaks, {} long breaks",
+            cycles,
+            if cycles == 1 { "" } else { "s" },
+            count(|s| matches!(s, PomoSegment::Work(..))),
+            work_minutes,
+            count(|s| matches!(s, PomoSegment::ShortBreak(_))),
+            count(|s| matches!(s, PomoSegment::LongBreak(_))),
+        )
+    }
+
+    /// What `--quiet` prints at the end, like `Done: 10 minutes of work`.
+    fn done(&self) -> String {
+        let minutes = self.work_minutes();
+        format!(
+            "Done: {} minute{} of work",
+            minutes,
+            if minutes == 1 { "" } else { "s" }
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PomoInput {
+    Help,
+    Pause,
+    Quit,
+    /// Cycle what the gauge is labeled with
+    Label,
+    /// The terminal changed size. Nothing to do but redraw, which the event loops do right away
+    /// after any input; ratatui clears the screen when it notices the new size, so no stale
+    /// cells are left behind.
+    Resize,
+    Skip,
+    /// Go back to the segment before the current one and start it over
+    Previous,
+    /// Show or hide the clock times the segment and the cycle end at
+    EndTimes,
+}
+
+impl PomoInput {
+    /// Translate a key press, or a left click on one of the `buttons` from the last draw.
+    fn from_event(event: Event, keys: &Keymap, buttons: &Buttons) -> Result<Self, ()> {
+        match event {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column,
+                row,
+                ..
+            }) => buttons.at(column, row).ok_or(()),
+            event => keys.input(event).ok_or(()),
+        }
+    }
+}
+
+/// Where the clickable buttons were drawn, so that a click can be traced back to the input it
+/// stands for. Empty unless `--mouse` is on.
+#[derive(Debug, Default)]
+struct Buttons(Vec<(Rect, PomoInput)>);
+
+impl Buttons {
+    fn at(&self, column: u16, row: u16) -> Option<PomoInput> {
+        self.0
+            .iter()
+            .find(|(area, _)| {
+                (area.left()..area.right()).contains(&column)
+                    && (area.top()..area.bottom()).contains(&row)
+            })
+            .map(|(_, input)| *input)
+    }
+}
+
+/// Settings that change how the TUI is drawn. All but the gauge label and the end times hold for
+/// a whole session.
+#[derive(Clone, Debug)]
+struct DisplayOptions {
+    theme: Theme,
+    keys: Keymap,
+    /// Draw the buttons for `--mouse`
+    buttons: bool,
+    gauge_label: GaugeLabel,
+    /// Show the time left in `big_digits` for `--big`
+    big_digits: bool,
+    precision: Precision,
+    /// Show when the segment and cycle end on the cycle line
+    end_times: bool,
+    /// Draw only the gauge, as on a short terminal, for `--quiet`
+    compact: bool,
+    /// Dim the colors by the hour they're drawn at, for `--auto-dim`
+    auto_dim: bool,
+}
+
+/// How finely the gauge label shows a time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum Precision {
+    /// Minutes and seconds, like `24:31`
+    #[default]
+    Seconds,
+    /// Down to tenths of a second, like `24:31.4`
+    Deciseconds,
+    /// Rounded to the nearest minute, like `25m`
+    Minutes,
+}
+
+impl Precision {
+    fn format(self, duration: Duration) -> String {
+        match self {
+            Precision::Seconds => format_mmss(duration),
+            Precision::Deciseconds => format!(
+                "{}.{}",
+                format_mmss(duration),
+                duration.subsec_millis() / 100
+            ),
+            Precision::Minutes => format!("{:02}m", (duration.as_secs() + 30) / 60),
+        }
+    }
+
+    /// How often the screen has to change to keep up. The rest of it shows seconds, so that
+    /// is never any slower than every second.
+    fn redraw_step(self) -> Duration {
+        match self {
+            Precision::Deciseconds => Duration::from_millis(100),
+            Precision::Seconds | Precision::Minutes => Duration::from_secs(1),
+        }
+    }
+}
+
+/// What the pomo gauge is labeled with, changed with the label key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum GaugeLabel {
+    #[default]
+    Remaining,
+    Elapsed,
+    Percent,
+}
+
+impl GaugeLabel {
+    fn next(self) -> Self {
+        match self {
+            GaugeLabel::Remaining => GaugeLabel::Elapsed,
+            GaugeLabel::Elapsed => GaugeLabel::Percent,
+            GaugeLabel::Percent => GaugeLabel::Remaining,
+        }
+    }
+
+    fn text(self, total: Duration, remaining: Duration, precision: Precision) -> String {
+        match self {
+            GaugeLabel::Remaining => precision.format(remaining),
+            GaugeLabel::Elapsed => format!(
+                "{} elapsed",
+                precision.format(total.saturating_sub(remaining))
+            ),
+            GaugeLabel::Percent => format!("{:.0}%", progress_ratio(total, remaining) * 100.0),
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// Options with `theme`, or the terminal's own colors with `--no-color` or `NO_COLOR`.
+    fn new(theme: Theme, keys: Keymap, no_color: bool, buttons: bool) -> Self {
+        let use_color = !no_color && std::env::var_os("NO_COLOR").is_none();
+        Self {
+            theme: if use_color {
+                theme
+            } else {
+                Theme {
+                    ascii_only: theme.ascii_only,
+                    ..Theme::monochrome()
+                }
+            },
+            keys,
+            buttons,
+            gauge_label: GaugeLabel::default(),
+            big_digits: false,
+            precision: Precision::default(),
+            end_times: false,
+            compact: false,
+            auto_dim: false,
+        }
+    }
+}
+
+/// How many rows the segment list, gauge and cycle line need. A shorter terminal gets a compact
+/// layout with just the gauge.
+const FULL_LAYOUT_HEIGHT: u16 = 9;
+
+/// Draw the TUI widgets showing the current pomodoro segment and the progress in the current
+/// segment as a gauge with a countdown.
+fn render_countdown<B: Backend>(
+    f: &mut Frame<B>,
+    progress: SessionProgress,
+    state: CountdownState,
+    reminder: Option<&str>,
+    pause: PauseState,
+    show_help: bool,
+    options: &DisplayOptions,
+) -> Buttons {
+    let SessionProgress {
+        segments_list,
+        i_segment,
+        outcomes,
+    } = progress;
+    let is_paused = pause != PauseState::Running;
+    let title = |title: &str| match pause {
+        PauseState::AutoPaused => format!("{} (AUTO-PAUSED)", title),
+        _ => gauge_title(title, is_paused),
+    };
+    let dimming = match options.auto_dim {
+        true => theme::dim_factor(Local::now().hour()),
+        false => 1.0,
+    };
+    let theme = &options.theme.dimmed(dimming);
+    let remaining = state.remaining;
+    let segment = &segments_list[i_segment % segments_list.len()];
+    let total = segment.duration();
+    let is_work = matches!(segment, PomoSegment::Work(..));
+    let color = theme::dim(
+        gauge_color(is_work, 1.0 - progress_ratio(total, remaining)),
+        dimming,
+    );
+    let progress_title = if is_work { "Progress" } else { "Break" };
+    let progress = progress_gauge(theme, progress_title, total, remaining, is_paused, color)
+        .label(
+            options
+                .gauge_label
+                .text(total, remaining, options.precision),
+        )
+        .block(segment_block(theme, is_work, color).title(title(progress_title)));
+    let in_cycle = cycle_remaining(segments_list, i_segment, remaining);
+    let mut cycle_text = format!("Total remaining: {}", format_hms(in_cycle));
+    let paused = state.total_paused();
+    if !paused.is_zero() {
+        cycle_text.push_str(&format!("   Paused for: {}", format_mmss(paused)));
+    }
+    if options.end_times {
+        cycle_text.push_str("   ");
+        cycle_text.push_str(&format_end_times(Local::now(), remaining, in_cycle));
+    }
+    if let Some(reminder) = reminder {
+        cycle_text.push_str("   ");
+        cycle_text.push_str(reminder);
+    }
+    if options.compact || f.size().height < FULL_LAYOUT_HEIGHT {
+        // Only the gauge, with a border if there are rows for one, and the cycle line if it fits
+        let area = f.size();
+        let gauge_height = if area.height >= 3 && area.width >= 3 {
+            3
+        } else {
+            1
+        };
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(gauge_height), Constraint::Min(0)])
+            .split(area);
+        let gauge = if gauge_height == 3 {
+            progress_gauge(
+                theme,
+                &segment.to_string(),
+                total,
+                remaining,
+                is_paused,
+                color,
+            )
+            .label(
+                options
+                    .gauge_label
+                    .text(total, remaining, options.precision),
+            )
+            .block(segment_block(theme, is_work, color).title(title(&segment.to_string())))
+        } else {
+            bare_gauge(theme, total, remaining, is_paused, color)
+                .label(format_plain(segment, remaining, is_paused))
+        };
+        f.render_widget(gauge, rows[0]);
+        f.render_widget(widgets::Paragraph::new(cycle_text), rows[1]);
+        return Buttons::default();
+    }
+    let session = widgets::Paragraph::new(cycle_text)
+        .block(segment_block(theme, is_work, color).title("Cycle"));
+    let mut area = f.size();
+    if options.big_digits {
+        let digits = big_digits(&format_mmss(remaining));
+        let width = digits[0].chars().count() as u16;
+        // Only above the full layout, with a blank row under the digits; without the room for
+        // that, the gauge's label has to do.
+        let height = BIG_DIGIT_ROWS as u16 + 1;
+        if area.height >= FULL_LAYOUT_HEIGHT + height && area.width >= width + 8 {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(height), Constraint::Min(0)])
+                .split(area);
+            let lines: Vec<Spans> = digits.into_iter().map(Spans::from).collect();
+            let mut style = Style::default().fg(theme.gauge.unwrap_or(color));
+            if let Some(background) = theme.gauge_background {
+                style = style.bg(background);
+            }
+            let big = widgets::Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .style(style);
+            f.render_widget(big, rows[0]);
+            area = rows[1];
+        }
+    }
+    // Margins only take rows left over once the segments, gauge and help all fit.
+    let vertical_margin = area
+        .height
+        .saturating_sub(FULL_LAYOUT_HEIGHT + 9)
+        .div_euclid(4);
+    let chunks_0 = Layout::default()
+        .horizontal_margin(4)
+        .vertical_margin(vertical_margin)
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(9),
+            Constraint::Length(9),
+            Constraint::Min(0),
+        ])
+        .split(area);
+    // Wide enough for the longest label after its mark and the highlight symbol, with a space
+    // before the border.
+    let label_width = segments_list
+        .iter()
+        .map(|segment| segment_label(segment).chars().count())
+        .max()
+        .unwrap_or(0) as u16
+        + 2;
+    let chunks_0_0 = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length((label_width + 6).max(18)),
+            Constraint::Min(0),
+        ])
+        .split(chunks_0[0]);
+    let chunks_0_0_1 = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(chunks_0_0[1]);
+    let chunks_0_1 = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(HELP_WIDTH), Constraint::Min(0)])
+        .split(chunks_0[1]);
+    // Inside the border, so that the current segment is in the middle of what's visible.
+    let n_segment_rows = chunks_0_0[0].height.saturating_sub(2) as usize;
+    let rows = segment_rows(segments_list, i_segment, n_segment_rows);
+    let current_row = rows
+        .iter()
+        .position(|row| row.is_some_and(|(i, _)| i == i_segment));
+    let rows = rows.into_iter().map(|row| {
+        let cell = match row {
+            Some((i, segment)) => widgets::Cell::from(segment_row(
+                segment,
+                outcomes.get(i).copied().flatten(),
+                theme,
+            )),
+            None => widgets::Cell::from(""),
+        };
+        widgets::Row::new(vec![cell])
+    });
+    let segment_widths = [Constraint::Length(label_width)];
+    let segments_table = widgets::Table::new(rows.collect::<Vec<_>>())
+        .highlight_style(theme.emphasized(theme.highlight))
+        .highlight_symbol(" > ")
+        .block(theme.block().title("Current segment"))
+        .widths(&segment_widths);
+    let mut segments_table_state = widgets::TableState::default();
+    segments_table_state.select(current_row);
+    f.render_stateful_widget(segments_table, chunks_0_0[0], &mut segments_table_state);
+    f.render_widget(progress, chunks_0_0_1[0]);
+    f.render_widget(session, chunks_0_0_1[1]);
+    if show_help {
+        let rows = [
+            (PomoInput::Pause, "Pause timer"),
+            (PomoInput::Skip, "Skip to next segment"),
+            (PomoInput::Previous, "Back to last segment"),
+            (PomoInput::Label, "Change gauge label"),
+            (PomoInput::EndTimes, "Toggle end times"),
+        ];
+        let help_table = help_table(&rows, options);
+        f.render_widget(help_table, chunks_0_1[0]);
+    }
+    if options.buttons {
+        return render_buttons(f, chunks_0_1[1], theme, is_paused, "Skip");
+    }
+    Buttons::default()
+}
+
+/// When the segment and the cycle will end if the countdown carries on from `now`, like
+/// `Ends at 14:05   Cycle ends at 15:40`. Drawn afresh each time, so pausing pushes both back.
+fn format_end_times(now: DateTime<Local>, remaining: Duration, in_cycle: Duration) -> String {
+    let at = |left: Duration| {
+        chrono::Duration::from_std(left)
+            .ok()
+            .and_then(|left| now.checked_add_signed(left))
+            .map_or("--:--".to_string(), |end| end.format("%H:%M").to_string())
+    };
+    format!("Ends at {}   Cycle ends at {}", at(remaining), at(in_cycle))
+}
+
+/// Whether the countdown is going, and if not, what stopped it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PauseState {
+    Running,
+    Paused,
+    /// Paused by `--auto-pause` for want of a key, until the next one
+    AutoPaused,
+}
+
+/// The segments of a session and how it's going through them, for drawing the list of segments.
+#[derive(Clone, Copy)]
+struct SessionProgress<'a> {
+    segments_list: &'a [PomoSegment],
+    /// Index of the current segment, counting from the start of the session
+    i_segment: usize,
+    outcomes: &'a [Option<Outcome>],
+}
+
+/// What goes in each of `rows` rows of the segment list, with the index of the segment from the
+/// start of the session. The current segment keeps to the middle row, so the list moves up by
+/// exactly one row each time a segment ends, with those done above and those to come below,
+/// going on into the next cycle after the last segment of this one. Rows above the first segment
+/// of the session are blank.
+fn segment_rows(
+    segments: &[PomoSegment],
+    current: usize,
+    rows: usize,
+) -> Vec<Option<(usize, &PomoSegment)>> {
+    let above = rows.saturating_sub(1) / 2;
+    (0..rows)
+        .map(|row| {
+            let i = (current + row).checked_sub(above)?;
+            Some((i, &segments[i % segments.len()]))
+        })
+        .collect()
+}
+
+/// A segment's name and length, like `Work (25m)`.
+fn segment_label(segment: &PomoSegment) -> String {
+    format!(
+        "{} ({})",
+        segment,
+        format_minutes(segment.duration().as_secs() / 60)
+    )
+}
+
+/// A segment's label, after a ✓ if it ran to completion or a dim ✗ if it was skipped.
+fn segment_row(segment: &PomoSegment, outcome: Option<Outcome>, theme: &Theme) -> Spans<'static> {
+    let mark = match outcome {
+        Some(Outcome::Completed) => Span::styled("✓ ", theme.emphasized(theme.done)),
+        Some(Outcome::Skipped) => Span::styled("✗ ", Style::default().add_modifier(Modifier::DIM)),
+        Some(Outcome::Quit) | None => Span::raw("  "),
+    };
+    Spans::from(vec![mark, Span::raw(segment_label(segment))])
+}
+
+fn is_ctrl_c(event: Event) -> bool {
+    matches!(
+        event,
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('c'),
+            modifiers,
+        }) if modifiers.contains(KeyModifiers::CONTROL)
+    )
+}
+
+/// Show `text`, like a question to answer, in a box in the middle of the screen.
+fn render_popup<B: Backend>(f: &mut Frame<B>, theme: &Theme, text: &str) {
+    let area = f.size();
+    let width = text.chars().count() as u16 + 8;
+    let (width, height) = (area.width.min(width), area.height.min(3));
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let question = widgets::Paragraph::new(text.to_string())
+        .alignment(Alignment::Center)
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .block(theme.block());
+    f.render_widget(widgets::Clear, popup);
+    f.render_widget(question, popup);
+}
+
+/// Fill the screen with inverted colors, with `text` in the middle.
+fn render_flash<B: Backend>(f: &mut Frame<B>, text: &str) {
+    let area = f.size();
+    let lines: Vec<Spans> = repeat_n(Spans::default(), (area.height / 2).into())
+        .chain(once(Spans::from(text.to_string())))
+        .collect();
+    let flash = widgets::Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD));
+    f.render_widget(flash, area);
+}
+
+/// Draw pause, skip and quit buttons in a row along the top of `area`.
+fn render_buttons<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    theme: &Theme,
+    is_paused: bool,
+    skip_label: &str,
+) -> Buttons {
+    let labels = [
+        (if is_paused { "Resume" } else { "Pause" }, PomoInput::Pause),
+        (skip_label, PomoInput::Skip),
+        ("Quit", PomoInput::Quit),
+    ];
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Min(0),
+        ])
+        .split(Rect {
+            height: area.height.min(3),
+            ..area
+        });
+    let buttons = labels
+        .into_iter()
+        .zip(chunks.iter())
+        .map(|((label, input), &chunk)| {
+            let button = widgets::Paragraph::new(label)
+                .alignment(Alignment::Center)
+                .block(theme.block());
+            f.render_widget(button, chunk);
+            (chunk, input)
+        })
+        .collect();
+    Buttons(buttons)
+}
+
+/// A gauge titled `title` that fills up over `total` and is labeled with the time remaining. It
+/// takes `color` unless the theme sets its own.
+fn progress_gauge(
+    theme: &Theme,
+    title: &str,
+    total: Duration,
+    remaining: Duration,
+    is_paused: bool,
+    color: Color,
+) -> widgets::Gauge<'static> {
+    bare_gauge(theme, total, remaining, is_paused, color)
+        .block(theme.block().title(gauge_title(title, is_paused)))
+}
+
+fn gauge_title(title: &str, is_paused: bool) -> String {
+    if is_paused {
+        format!("{} (PAUSED)", title)
+    } else {
+        title.to_string()
+    }
+}
+
+/// The border around a segment's widgets. A break's is doubled and takes the gauge's color, so
+/// that it stands apart from work even with colors off.
+fn segment_block(theme: &Theme, is_work: bool, color: Color) -> widgets::Block<'static> {
+    if is_work {
+        theme.block()
+    } else {
+        theme
+            .block()
+            .border_type(widgets::BorderType::Double)
+            .border_style(Style::default().fg(theme.gauge.unwrap_or(color)))
+    }
+}
+
+/// `progress_gauge` without the border and title, for a single row.
+fn bare_gauge(
+    theme: &Theme,
+    total: Duration,
+    remaining: Duration,
+    is_paused: bool,
+    color: Color,
+) -> widgets::Gauge<'static> {
+    let color = theme
+        .gauge_paused
+        .filter(|_| is_paused)
+        .or(theme.gauge)
+        .unwrap_or(color);
+    let mut style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+    if let Some(background) = theme.gauge_background {
+        style = style.bg(background);
+    }
+    widgets::Gauge::default()
+        .gauge_style(style)
+        .label(format_mmss(remaining))
+        .ratio(progress_ratio(total, remaining))
+}
+
+/// How much of `total` has gone by, from 0.0 to 1.0. A zero-length countdown is already over.
+fn progress_ratio(total: Duration, remaining: Duration) -> f64 {
+    if total.is_zero() {
+        return 1.0;
+    }
+    let elapsed = total.saturating_sub(remaining);
+    (elapsed.as_secs_f64() / total.as_secs_f64()).clamp(0.0, 1.0)
+}
+
+/// Work goes from green through yellow to red as it runs out, and breaks from blue through cyan
+/// to green, so the kind of segment and how close it is to ending both show at a glance.
+fn gauge_color(work: bool, remaining_ratio: f64) -> Color {
+    match (work, remaining_ratio) {
+        (true, r) if r > 0.5 => Color::Green,
+        (true, r) if r >= 0.2 => Color::Yellow,
+        (true, _) => Color::Red,
+        (false, r) if r > 0.5 => Color::Blue,
+        (false, r) if r >= 0.2 => Color::Cyan,
+        (false, _) => Color::Green,
+    }
+}
+
+/// Wide enough for a `help_table`: both columns, the space between them and the borders.
+const HELP_WIDTH: u16 = 35;
+
+/// The keyboard shortcuts shown when the user presses 'h': help and quit, and then `rows` for
+/// whatever else the screen responds to. The `s` key skips a segment when counting down and
+/// records a lap in stopwatch mode, for example, and an alarm can't be paused.
+fn help_table<'a>(rows: &[(PomoInput, &'a str)], options: &DisplayOptions) -> widgets::Table<'a> {
+    let theme = &options.theme;
+    let key_style = theme.emphasized(theme.help_key);
+    let always = [
+        (PomoInput::Help, "Toggle this help"),
+        (PomoInput::Quit, "Quit"),
+    ];
+    widgets::Table::new(always.iter().chain(rows).map(|&(input, description)| {
+        widgets::Row::new(vec![
+            widgets::Cell::from(options.keys.describe(input)).style(key_style),
+            widgets::Cell::from(description),
+        ])
+    }))
+    .widths(&[Constraint::Length(12), Constraint::Length(20)])
+    .block(theme.block().title("Help"))
+}
+
+/// Like `render_countdown`, but for stopwatch mode: show the time elapsed so far and the laps
+/// recorded, most recent first.
+fn render_stopwatch<B: Backend>(
+    f: &mut Frame<B>,
+    elapsed: Duration,
+    laps: &[Duration],
+    is_paused: bool,
+    show_help: bool,
+    options: &DisplayOptions,
+) -> Buttons {
+    let theme = &options.theme;
+    let color = theme
+        .gauge_paused
+        .filter(|_| is_paused)
+        .or(theme.gauge)
+        .unwrap_or(Color::Green);
+    let elapsed_show_time = format_hms(elapsed);
+    let clock = widgets::Paragraph::new(elapsed_show_time.as_str())
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+        .block(theme.block().title(if is_paused {
+            "Elapsed (PAUSED)"
+        } else {
+            "Elapsed"
+        }));
+    let lap_rows = laps
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, lap)| {
+            widgets::Row::new(vec![
+                widgets::Cell::from(format!("{}", i + 1)),
+                widgets::Cell::from(format_hms(*lap)),
+            ])
+        })
+        .collect::<Vec<_>>();
+    let laps_table = widgets::Table::new(lap_rows)
+        .block(theme.block().title("Laps"))
+        .widths(&[Constraint::Length(4), Constraint::Length(10)]);
+    let vertical_margin = f.size().height.saturating_sub(10).div_euclid(4);
+    let chunks_0 = Layout::default()
+        .horizontal_margin(4)
+        .vertical_margin(vertical_margin)
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(9),
+            Constraint::Length(6),
+            Constraint::Min(0),
+        ])
+        .split(f.size());
+    let chunks_0_0 = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(18), Constraint::Min(0)])
+        .split(chunks_0[0]);
+    let chunks_0_1 = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(HELP_WIDTH), Constraint::Min(0)])
+        .split(chunks_0[1]);
+    f.render_widget(laps_table, chunks_0_0[0]);
+    f.render_widget(clock, chunks_0_0[1]);
+    if show_help {
+        f.render_widget(
+            help_table(
+                &[
+                    (PomoInput::Pause, "Pause timer"),
+                    (PomoInput::Skip, "Record a lap"),
+                ],
+                options,
+            ),
+            chunks_0_1[0],
+        );
+    }
+    if options.buttons {
+        return render_buttons(f, chunks_0_1[1], theme, is_paused, "Lap");
+    }
+    Buttons::default()
+}
+
+/// Time left in the current cycle: what remains of the current segment plus the full length of
+/// every segment after it, up to and including the last segment of the cycle. Saturates rather
+/// than overflowing, since segments can be made as long as a `u64` of minutes.
+fn cycle_remaining(
+    segments_list: &[PomoSegment],
+    i_segment: usize,
+    remaining: Duration,
+) -> Duration {
+    segments_list[i_segment % segments_list.len() + 1..]
+        .iter()
+        .map(PomoSegment::duration)
+        .fold(remaining, Duration::saturating_add)
+}
+
+/// One cycle of segments for `--show-schedule`, e.g.
+/// `Work 25m, Short break 5m, Work 25m, Long break 15m` and then `Total: 1h 10m` on a second line.
+fn format_schedule(segments_list: &[PomoSegment]) -> String {
+    let minutes = |segment: &PomoSegment| segment.duration().as_secs() / 60;
+    let segments = segments_list
+        .iter()
+        .map(|segment| format!("{} {}", segment, format_minutes(minutes(segment))))
+        .join(", ");
+    let total = segments_list
+        .iter()
+        .map(minutes)
+        .fold(0, u64::saturating_add);
+    format!("{}\nTotal: {}", segments, format_minutes(total))
+}
+
+/// `--print-config`'s output: the settings as TOML, after comments saying what the cycle comes
+/// to. If the cycle was `custom`, made some way other than from the lengths in the settings, the
+/// comments say so, since pasting the settings alone wouldn't give the same cycle.
+fn format_config(
+    resolved: &ResolvedPomoConfig,
+    segments_list: &[PomoSegment],
+    custom: bool,
+) -> Result<String, toml::ser::Error> {
+    let total: u64 = segments_list
+        .iter()
+        .map(|segment| segment.duration().as_secs() / 60)
+        .fold(0, u64::saturating_add);
+    let mut out = format!(
+        "# {} segments a cycle, {} in all\n",
+        segments_list.len(),
+        format_minutes(total)
+    );
+    if custom {
+        out.push_str("# (the segments come from --sequence, --tasks or --no-long-break)\n");
+    }
+    out.push_str(&resolved.to_toml()?);
+    Ok(out)
+}
+
+/// Format a number of minutes like `45m`, `1h 30m` or `2h`.
+fn format_minutes(minutes: u64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+/// The `--plain` status line, e.g. `[Work] 24:31 remaining`. Minutes are not wrapped into hours,
+/// so the line keeps the same shape for long segments.
+fn format_plain(segment: &PomoSegment, remaining: Duration, is_paused: bool) -> String {
+    format!(
+        "[{}] {} remaining{}",
+        segment,
+        format_mmss(remaining),
+        if is_paused { " (paused)" } else { "" }
+    )
+}
+
+/// How tall `big_digits` are
+const BIG_DIGIT_ROWS: usize = 7;
+
+/// `text` in block digits, as rows of the same width. Only digits and colons have glyphs; anything
+/// else is left blank.
+fn big_digits(text: &str) -> Vec<String> {
+    const DIGITS: [[&str; BIG_DIGIT_ROWS]; 10] = [
+        [
+            "█████",
+            "█   █",
+            "█   █",
+            "█   █",
+            "█   █",
+            "█   █",
+            "█████",
+        ],
+        [
+            "  █  ",
+            " ██  ",
+            "  █  ",
+            "  █  ",
+            "  █  ",
+            "  █  ",
+            " ███ ",
+        ],
+        [
+            "█████",
+            "    █",
+            "    █",
+            "█████",
+            "█    ",
+            "█    ",
+            "█████",
+        ],
+        [
+            "█████",
+            "    █",
+            "    █",
+            " ████",
+            "    █",
+            "    █",
+            "█████",
+        ],
+        [
+            "█   █",
+            "█   █",
+            "█   █",
+            "█████",
+            "    █",
+            "    █",
+            "    █",
+        ],
+        [
+            "█████",
+            "█    ",
+            "█    ",
+            "█████",
+            "    █",
+            "    █",
+            "█████",
+        ],
+        [
+            "█████",
+            "█    ",
+            "█    ",
+            "█████",
+            "█   █",
+            "█   █",
+            "█████",
+        ],
+        [
+            "█████",
+            "    █",
+            "   █ ",
+            "  █  ",
+            "  █  ",
+            "  █  ",
+            "  █  ",
+        ],
+        [
+            "█████",
+            "█   █",
+            "█   █",
+            "█████",
+            "█   █",
+            "█   █",
+            "█████",
+        ],
+        [
+            "█████",
+            "█   █",
+            "█   █",
+            "█████",
+            "    █",
+            "    █",
+            "█████",
+        ],
+    ];
+    const COLON: [&str; BIG_DIGIT_ROWS] = [" ", " ", "█", " ", "█", " ", " "];
+    const BLANK: [&str; BIG_DIGIT_ROWS] = ["     "; BIG_DIGIT_ROWS];
+    let glyphs: Vec<&[&str; BIG_DIGIT_ROWS]> = text
+        .chars()
+        .map(|c| match c {
+            ':' => &COLON,
+            c => c
+                .to_digit(10)
+                .map_or(&BLANK, |digit| &DIGITS[digit as usize]),
+        })
+        .collect();
+    (0..BIG_DIGIT_ROWS)
+        .map(|row| glyphs.iter().map(|glyph| glyph[row]).join(" "))
+        .collect()
+}
+
+/// Format a duration as minutes and seconds, like `24:31`.
+fn format_mmss(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+fn format_hms(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Show a desktop notification announcing that `finished` is over and `next` is starting.
+fn notify_transition(
+    finished: &PomoSegment,
+    next: &PomoSegment,
+) -> Result<(), notify_rust::error::Error> {
+    let done = match finished {
+        PomoSegment::Work(..) => "Work done",
+        PomoSegment::ShortBreak(_) => "Short break over",
+        PomoSegment::LongBreak(_) => "Long break over",
+    };
+    let up_next = match next {
+        PomoSegment::Work(..) => "back to work",
+        PomoSegment::ShortBreak(_) => "take a break",
+        PomoSegment::LongBreak(_) => "take a long break",
+    };
+    Notification::new()
+        .summary(&format!("{} — {}", done, up_next))
+        .body(&format!(
+            "Next: {} ({} min)",
+            next,
+            next.duration().as_secs() / 60
+        ))
+        .show()?;
+    Ok(())
+}
+
+/// Countdown to zero, sending the remaining and paused time to the given transmit channel. Watches
+/// for pauses on the given watch channel and returns when anything is sent on the cancel channel.
+///
+/// Remaining time is measured against the clock rather than by counting ticks, so it stays
+/// accurate however often the countdown is paused; ticks only pace the updates.
+async fn countdown(
+    duration: Duration,
+    tick_rate: Duration,
+    tx_state: watch::Sender<CountdownState>,
+    mut rx_paused: watch::Receiver<bool>,
+    mut rx_cancel: mpsc::Receiver<()>,
+) -> Result<(), KitError> {
+    let mut interval = time::interval(tick_rate);
+    // Delay ticks when the countdown is paused
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // Running time accumulated before the most recent resume
+    let mut elapsed_before_resume = Duration::ZERO;
+    let mut resumed_at = Instant::now();
+    let mut paused = Duration::ZERO;
+    loop {
+        let elapsed = elapsed_before_resume + resumed_at.elapsed();
+        let remaining = duration.saturating_sub(elapsed);
+        tx_state.send(CountdownState {
+            remaining,
+            paused,
+            paused_since: None,
+        })?;
+        if elapsed >= duration {
+            log::debug!("countdown of {:?} finished", duration);
+            break;
+        }
+        if *rx_paused.borrow_and_update() {
+            log::debug!("countdown paused after {:?}", elapsed);
+            elapsed_before_resume = elapsed;
+            let paused_at = Instant::now();
+            tx_state.send(CountdownState {
+                remaining,
+                paused,
+                paused_since: Some(paused_at),
+            })?;
+            tokio::select! {
+                _ = rx_cancel.recv() => {
+                    log::debug!("countdown cancelled while paused");
+                    break;
+                }
+                resumed = rx_paused.wait_for(|paused| !paused) => {
+                    resumed?;
+                }
+            }
+            paused += paused_at.elapsed();
+            log::debug!("countdown resumed, paused for {:?} in all", paused);
+            resumed_at = Instant::now();
+            continue;
+        }
+        tokio::select! {
+            _ = rx_cancel.recv() => {
+                log::debug!("countdown cancelled after {:?}", elapsed);
+                break;
+            }
+            changed = rx_paused.changed() => {
+                changed?;
+            }
+            _ = interval.tick() => {}
+            _ = time::sleep(duration - elapsed) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+
+    use super::*;
+
+    #[test]
+    fn test_cycle_remaining() {
+        let segments = vec![
+            PomoSegment::Work(25, None),
+            PomoSegment::ShortBreak(5),
+            PomoSegment::Work(25, None),
+            PomoSegment::LongBreak(15),
+        ];
+        let half_minute = Duration::from_secs(30);
+        assert_eq!(
+            cycle_remaining(&segments, 0, half_minute),
+            Duration::from_secs(30 + 45 * 60)
+        );
+        assert_eq!(cycle_remaining(&segments, 3, half_minute), half_minute);
+        // the segment index keeps counting up as the cycle repeats
+        assert_eq!(
+            cycle_remaining(&segments, 6, half_minute),
+            Duration::from_secs(30 + 15 * 60)
+        );
+        let huge = vec![PomoSegment::Work(u64::MAX, None); 3];
+        assert_eq!(cycle_remaining(&huge, 0, Duration::MAX), Duration::MAX);
+    }
+
+    #[test]
+    fn test_resume_point() {
+        let command = PomoCommand::parse_from(["pomo", "--resume", "--repeat", "2"]);
+        let segments = command.default_segments(&PomoConfig::default());
+        let saved = |segment_index, remaining_secs| SavedState {
+            saved_at: Local::now(),
+            schedule: format_schedule(&segments),
+            segment_index,
+            remaining_secs,
+        };
+        assert_eq!(
+            command.resume_point(Some(saved(8, 90)), &segments),
+            Some((8, Duration::from_secs(90)))
+        );
+        assert_eq!(command.resume_point(None, &segments), None);
+        // The segment at index 1 is a 5 minute break, and index 12 would be in a third cycle.
+        assert_eq!(command.resume_point(Some(saved(1, 600)), &segments), None);
+        assert_eq!(command.resume_point(Some(saved(12, 90)), &segments), None);
+        let other = PomoCommand::parse_from(["pomo", "--time", "50"]);
+        let other_segments = other.default_segments(&PomoConfig::default());
+        assert_eq!(
+            command.resume_point(Some(saved(0, 90)), &other_segments),
+            None
+        );
+    }
+
+    #[test]
+    fn test_session_summary() {
+        let session = Session {
+            completed: vec![
+                PomoSegment::Work(25, None),
+                PomoSegment::ShortBreak(5),
+                PomoSegment::Work(25, None),
+                PomoSegment::LongBreak(15),
+            ],
+            finished: true,
+            ..Session::default()
+        };
+        assert_eq!(
+            session.summary(1),
+            "Completed 1 cycle: 2 work segments (50 min), 1 short breaks, 1 long breaks"
+        );
+        assert_eq!(session.done(), "Done: 50 minutes of work");
+        let quiet = PomoCommand::parse_from(["pomo", "--quiet"]);
+        assert_eq!(quiet.cycles(), Some(1));
+        assert!(
+            quiet
+                .display_options(Theme::default(), Keymap::default())
+                .compact
+        );
+        let quiet = PomoCommand::parse_from(["pomo", "--quiet", "--repeat", "2"]);
+        assert_eq!(quiet.cycles(), Some(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_countdown_excludes_paused_time() {
+        let duration = Duration::from_secs(10);
+        let (tx_state, rx_state) = watch::channel(CountdownState::new(duration));
+        let (tx_paused, rx_paused) = watch::channel(false);
+        let (_tx_cancel, rx_cancel) = mpsc::channel(1);
+        let start = Instant::now();
+        let handle = tokio::spawn(countdown(
+            duration, TICK_RATE, tx_state, rx_paused, rx_cancel,
+        ));
+        // pause in the middle of ticks so that tick counting would drift
+        for _ in 0..5 {
+            time::sleep(Duration::from_millis(1050)).await;
+            tx_paused.send(true).unwrap();
+            time::sleep(Duration::from_secs(3)).await;
+            tx_paused.send(false).unwrap();
+        }
+        time::sleep(Duration::from_secs(4)).await;
+        let remaining = rx_state.borrow().remaining;
+        assert!(
+            remaining.abs_diff(Duration::from_millis(750)) <= Duration::from_millis(100),
+            "remaining: {:?}",
+            remaining
+        );
+        handle.await.unwrap().unwrap();
+        let total = start.elapsed();
+        assert!(
+            total.abs_diff(Duration::from_secs(25)) <= Duration::from_millis(100),
+            "total: {:?}",
+            total
+        );
+        assert_eq!(rx_state.borrow().remaining, Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_countdown_tracks_paused_time() {
+        let countdown = Countdown::start(Duration::from_secs(10), false, TICK_RATE);
+        time::sleep(Duration::from_secs(2)).await;
+        countdown.tx_paused.send(true).unwrap();
+        time::sleep(Duration::from_secs(3)).await;
+        // still paused, so the pause so far counts
+        let paused = countdown.state().total_paused();
+        assert!(paused.abs_diff(Duration::from_secs(3)) <= Duration::from_millis(100));
+        countdown.tx_paused.send(false).unwrap();
+        time::sleep(Duration::from_secs(1)).await;
+        let state = countdown.state();
+        assert_eq!(state.paused_since, None);
+        assert!(state.paused.abs_diff(Duration::from_secs(3)) <= Duration::from_millis(100));
+        assert!(state.remaining.abs_diff(Duration::from_secs(7)) <= Duration::from_millis(100));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_countdown_starts_paused() {
+        let countdown = Countdown::start(Duration::from_secs(10), true, TICK_RATE);
+        time::sleep(Duration::from_secs(5)).await;
+        let state = countdown.state();
+        assert_eq!(state.remaining, Duration::from_secs(10));
+        assert!(state.paused_since.is_some());
+        countdown.tx_paused.send(false).unwrap();
+        time::sleep(Duration::from_secs(4)).await;
+        let remaining = countdown.state().remaining;
+        assert!(remaining.abs_diff(Duration::from_secs(6)) <= Duration::from_millis(100));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reminder() {
+        let command =
+            PomoCommand::parse_from(["pomo", "--reminder", "1", "--reminder-text", "Stretch"]);
+        let segments = command.default_segments(&PomoConfig::default());
+        let alerts = Alerts::default();
+        let mut runner = SegmentRunner::new(&command, &segments, None, &alerts).unwrap();
+        assert_eq!(runner.reminder_text().unwrap(), "Reminder in: 01:00");
+        time::sleep(Duration::from_millis(60_050)).await;
+        assert!(runner.tick().unwrap().is_continue());
+        assert_eq!(runner.reminder_text().unwrap(), "Stretch!");
+        time::sleep(REMINDER_SHOWN).await;
+        assert!(runner.tick().unwrap().is_continue());
+        assert_eq!(runner.reminder_text().unwrap(), "Reminder in: 00:50");
+        assert_eq!(runner.i, 0);
+    }
+
+    /// `pomo` run with `args`, logging to a temp file named after `name` which is removed on drop,
+    /// along with everything a `CountdownApp` over its default segments borrows.
+    struct TestApp {
+        log: std::path::PathBuf,
+        command: PomoCommand,
+        segments: Vec<PomoSegment>,
+        alerts: Alerts,
+    }
+
+    impl TestApp {
+        fn new(name: &str, args: &[&str]) -> Self {
+            let log =
+                std::env::temp_dir().join(format!("kit-{}-test-{}", name, std::process::id()));
+            let command = PomoCommand::parse_from(
+                ["pomo", "--log", log.to_str().unwrap()]
+                    .into_iter()
+                    .chain(args.iter().copied()),
+            );
+            let segments = command.default_segments(&PomoConfig::default());
+            TestApp {
+                log,
+                command,
+                segments,
+                alerts: Alerts::default(),
+            }
+        }
+
+        fn app(&self, start: Option<(usize, Duration)>) -> CountdownApp<'_> {
+            CountdownApp {
+                runner: SegmentRunner::new(&self.command, &self.segments, start, &self.alerts)
+                    .unwrap(),
+                options: DisplayOptions::new(Theme::default(), Keymap::default(), false, false),
+                buttons: Buttons::default(),
+                flash_started: None,
+                drawn: None,
+                confirming_quit: false,
+                paused_by_suspend: false,
+            }
+        }
+    }
+
+    impl Drop for TestApp {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.log);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_previous_segment() {
+        let test = TestApp::new("previous", &[]);
+        let mut runner = test.app(Some((2, Duration::from_secs(60)))).runner;
+        runner.session.outcomes = vec![Some(Outcome::Completed), Some(Outcome::Skipped)];
+        assert!(runner
+            .handle_input(PomoInput::Previous)
+            .unwrap()
+            .is_continue());
+        assert_eq!(runner.i, 1);
+        assert!(matches!(runner.segment(), PomoSegment::ShortBreak(5)));
+        assert_eq!(runner.remaining(), Duration::from_secs(5 * 60));
+        assert_eq!(runner.session.outcomes, [Some(Outcome::Completed)]);
+        assert!(runner
+            .handle_input(PomoInput::Previous)
+            .unwrap()
+            .is_continue());
+        assert!(runner
+            .handle_input(PomoInput::Previous)
+            .unwrap()
+            .is_continue());
+        assert_eq!(runner.i, 0);
+        assert_eq!(runner.remaining(), Duration::from_secs(25 * 60));
+        assert!(runner.session.outcomes.is_empty());
+        // The work segments left behind were both logged as cut short.
+        let logged = std::fs::read_to_string(&test.log).unwrap();
+        assert_eq!(logged.lines().count(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_confirm_transitions() {
+        let test = TestApp::new("confirm", &["--confirm-transitions"]);
+        let mut runner = test.app(None).runner;
+        assert!(!runner.waiting);
+        assert!(runner.handle_input(PomoInput::Skip).unwrap().is_continue());
+        assert_eq!(runner.i, 1);
+        assert!(runner.waiting && runner.is_paused);
+        time::sleep(Duration::from_secs(30)).await;
+        assert!(runner.tick().unwrap().is_continue());
+        assert_eq!(runner.remaining(), Duration::from_secs(5 * 60));
+        assert!(runner.handle_input(PomoInput::Pause).unwrap().is_continue());
+        assert!(!runner.waiting && !runner.is_paused);
+        time::sleep(Duration::from_secs(10)).await;
+        assert_eq!(runner.countdown.state().total_paused(), Duration::ZERO);
+        assert!(runner.remaining() < Duration::from_secs(5 * 60));
+        // Quitting while waiting for the next work segment logs only the skipped one.
+        assert!(runner.handle_input(PomoInput::Skip).unwrap().is_continue());
+        assert!(runner.waiting);
+        assert!(runner.handle_input(PomoInput::Quit).unwrap().is_break());
+        let logged = std::fs::read_to_string(&test.log).unwrap();
+        assert_eq!(logged.lines().count(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_auto_pause() {
+        let test = TestApp::new("auto-pause", &["--auto-pause", "60"]);
+        let mut runner = test.app(None).runner;
+        time::sleep(Duration::from_secs(30)).await;
+        assert!(!runner.key_pressed(Some(PomoInput::Help)).unwrap());
+        time::sleep(Duration::from_secs(45)).await;
+        assert!(runner.tick().unwrap().is_continue());
+        assert_eq!(runner.pause_state(), PauseState::Running);
+        time::sleep(Duration::from_secs(16)).await;
+        assert!(runner.tick().unwrap().is_continue());
+        assert_eq!(runner.pause_state(), PauseState::AutoPaused);
+        // The key that resumes does nothing else, unless it's to quit.
+        assert!(runner.key_pressed(Some(PomoInput::Skip)).unwrap());
+        assert_eq!(runner.pause_state(), PauseState::Running);
+        assert_eq!(runner.i, 0);
+        time::sleep(Duration::from_secs(61)).await;
+        assert!(runner.tick().unwrap().is_continue());
+        assert!(!runner.key_pressed(Some(PomoInput::Quit)).unwrap());
+        // Breaks go on whether anyone is there or not.
+        assert!(runner.handle_input(PomoInput::Skip).unwrap().is_continue());
+        time::sleep(Duration::from_secs(120)).await;
+        assert!(runner.tick().unwrap().is_continue());
+        assert_eq!(runner.pause_state(), PauseState::Running);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_strict_quit() {
+        let test = TestApp::new("strict", &["--strict"]);
+        let key = |c, modifiers| Event::Key(KeyEvent::new(KeyCode::Char(c), modifiers));
+        let mut work = test.app(None);
+        assert!(work
+            .handle_event(key('q', KeyModifiers::NONE))
+            .unwrap()
+            .is_continue());
+        assert!(work.confirming_quit);
+        assert!(work
+            .handle_event(Event::Resize(80, 24))
+            .unwrap()
+            .is_continue());
+        assert!(work
+            .handle_event(key('n', KeyModifiers::NONE))
+            .unwrap()
+            .is_continue());
+        assert!(!work.confirming_quit);
+        assert!(work
+            .handle_event(key('q', KeyModifiers::NONE))
+            .unwrap()
+            .is_continue());
+        assert!(work
+            .handle_event(key('y', KeyModifiers::NONE))
+            .unwrap()
+            .is_break());
+        let mut work = test.app(None);
+        assert!(work
+            .handle_event(key('c', KeyModifiers::CONTROL))
+            .unwrap()
+            .is_break());
+        let mut short_break = test.app(Some((1, Duration::from_secs(60))));
+        assert!(short_break
+            .handle_event(key('q', KeyModifiers::NONE))
+            .unwrap()
+            .is_break());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_countdown_app_frames() {
+        let test = TestApp::new("frames", &[]);
+        let mut app = test.app(None);
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+        let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        let events = crate::tui::scripted(vec![key('l'), key('h')], Duration::from_millis(1200));
+        crate::tui::event_loop(&mut app, &mut terminal, events)
+            .await
+            .unwrap();
+        let lines = crate::tui::drawn_lines(&terminal);
+        let has = |text: &str| lines.iter().any(|line| line.contains(text));
+        assert!(has(" >   Work (25m)"), "{:#?}", lines);
+        assert!(has("Short break (5m)"));
+        // Three seconds in, with the gauge switched to show the time elapsed
+        assert!(has("00:03 elapsed"));
+        assert!(has("Total remaining: 01:39:56"));
+        for row in ["Toggle this help", "Pause timer", "Back to last segment"] {
+            assert!(has(row), "no help row {:?}", row);
+        }
+    }
+
+    #[test]
+    fn test_until_deadline() {
+        let now =
+            NaiveDateTime::parse_from_str("2024-03-01 16:30:15", "%Y-%m-%d %H:%M:%S").unwrap();
+        let time = |s| parse_clock_time(s).unwrap();
+        assert_eq!(
+            until_deadline(now, time("17:00")),
+            Duration::from_secs(29 * 60 + 45)
+        );
+        assert_eq!(
+            until_deadline(now, time("09:00")),
+            Duration::from_secs(16 * 3600 + 29 * 60 + 45)
+        );
+        assert_eq!(
+            until_deadline(now.date().and_time(time("16:30")), time("16:30")),
+            Duration::from_secs(24 * 3600)
+        );
+        assert!(parse_clock_time("5pm").is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fit_before() {
+        let deadline = Some(Instant::now() + Duration::from_secs(600));
+        assert_eq!(
+            fit_before(Duration::from_secs(1500), deadline),
+            Duration::from_secs(600)
+        );
+        assert_eq!(
+            fit_before(Duration::from_secs(300), deadline),
+            Duration::from_secs(300)
+        );
+        assert_eq!(
+            fit_before(Duration::from_secs(1500), None),
+            Duration::from_secs(1500)
+        );
+    }
+
+    #[test]
+    fn test_format_hms() {
+        assert_eq!(format_hms(Duration::ZERO), "00:00:00");
+        assert_eq!(
+            format_hms(Duration::from_secs(3 * 3600 + 25 * 60 + 9)),
+            "03:25:09"
+        );
+    }
+
+    #[test]
+    fn test_resize_input() {
+        assert_eq!(
+            Keymap::default().input(Event::Resize(80, 24)),
+            Some(PomoInput::Resize)
+        );
+    }
+
+    #[test]
+    fn test_click_buttons() {
+        let buttons = Buttons(vec![
+            (Rect::new(4, 10, 10, 3), PomoInput::Pause),
+            (Rect::new(14, 10, 10, 3), PomoInput::Skip),
+        ]);
+        let keys = Keymap::default();
+        let click = |column, row| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        assert_eq!(
+            PomoInput::from_event(click(4, 10), &keys, &buttons),
+            Ok(PomoInput::Pause)
+        );
+        assert_eq!(
+            PomoInput::from_event(click(23, 12), &keys, &buttons),
+            Ok(PomoInput::Skip)
+        );
+        assert_eq!(
+            PomoInput::from_event(click(24, 12), &keys, &buttons),
+            Err(())
+        );
+        assert_eq!(
+            PomoInput::from_event(click(4, 13), &keys, &buttons),
+            Err(())
+        );
+    }
+
+    #[test]
+    fn test_render_countdown_short_terminals() {
+        let segments = [PomoSegment::Work(25, None), PomoSegment::ShortBreak(5)];
+        let state = CountdownState::new(Duration::from_secs(24 * 60 + 31));
+        let options = DisplayOptions::new(Theme::default(), Keymap::default(), false, true);
+        let progress = SessionProgress {
+            segments_list: &segments,
+            i_segment: 0,
+            outcomes: &[],
+        };
+        for height in 1..=16 {
+            for width in 1..=12 {
+                let backend = ratatui::backend::TestBackend::new(width, height);
+                let mut terminal = ratatui::Terminal::new(backend).unwrap();
+                terminal
+                    .draw(|f| {
+                        render_countdown(
+                            f,
+                            progress,
+                            state,
+                            None,
+                            PauseState::Running,
+                            true,
+                            &options,
+                        );
+                    })
+                    .unwrap();
+            }
+        }
+        let backend = ratatui::backend::TestBackend::new(40, 2);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                render_countdown(
+                    f,
+                    progress,
+                    state,
+                    None,
+                    PauseState::Paused,
+                    false,
+                    &options,
+                );
+            })
+            .unwrap();
+        let lines = crate::tui::drawn_lines(&terminal);
+        assert!(lines[0].contains("[Work] 24:31 remaining (paused)"));
+        assert!(lines[1].starts_with("Total remaining: 00:29:31"));
+    }
+
+    #[test]
+    fn test_render_huge_segments() {
+        let segments = [
+            PomoSegment::Work(300_000_000_000_000_000, None),
+            PomoSegment::ShortBreak(5),
+            PomoSegment::LongBreak(u64::MAX),
+        ];
+        let state = CountdownState::new(segments[0].duration());
+        let options = DisplayOptions {
+            end_times: true,
+            big_digits: true,
+            ..DisplayOptions::new(Theme::default(), Keymap::default(), false, false)
+        };
+        let progress = SessionProgress {
+            segments_list: &segments,
+            i_segment: 0,
+            outcomes: &[],
+        };
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 40)).unwrap();
+        terminal
+            .draw(|f| {
+                render_countdown(
+                    f,
+                    progress,
+                    state,
+                    None,
+                    PauseState::Running,
+                    false,
+                    &options,
+                );
+            })
+            .unwrap();
+        let lines = crate::tui::drawn_lines(&terminal);
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("300000000000000000:00")));
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("Total remaining: 5124095576030431:00:15")));
+    }
+
+    #[test]
+    fn test_render_segment_outcomes() {
+        let segments = [
+            PomoSegment::Work(25, None),
+            PomoSegment::ShortBreak(5),
+            PomoSegment::LongBreak(15),
+        ];
+        let state = CountdownState::new(Duration::from_secs(60));
+        let options = DisplayOptions::new(Theme::default(), Keymap::default(), false, false);
+        let progress = SessionProgress {
+            segments_list: &segments,
+            i_segment: 2,
+            outcomes: &[Some(Outcome::Completed), Some(Outcome::Skipped)],
+        };
+        let backend = ratatui::backend::TestBackend::new(60, 16);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                render_countdown(
+                    f,
+                    progress,
+                    state,
+                    None,
+                    PauseState::Running,
+                    false,
+                    &options,
+                );
+            })
+            .unwrap();
+        let lines = crate::tui::drawn_lines(&terminal);
+        let row = |text: &str| lines.iter().position(|line| line.contains(text));
+        let done = row("✓ Work (25m)").unwrap();
+        assert_eq!(row("✗ Short break (5m)"), Some(done + 1));
+        assert!(lines[done + 2].contains(">   Long break (15m) │"));
+        let x = lines[done].chars().position(|c| c == '✓').unwrap();
+        let cell = terminal.backend().buffer().get(x as u16, done as u16);
+        assert_eq!(cell.fg, Color::Green);
+        // The long break's gauge and cycle line are set apart from work's.
+        assert!(lines.iter().any(|line| line.contains("╔Break")));
+        assert!(lines.iter().any(|line| line.contains("╔Cycle")));
+        assert!(!lines.iter().any(|line| line.contains("Progress")));
+    }
+
+    #[test]
+    fn test_segment_rows() {
+        let segments = [
+            PomoSegment::Work(25, None),
+            PomoSegment::ShortBreak(5),
+            PomoSegment::Work(25, None),
+            PomoSegment::LongBreak(15),
+        ];
+        let labels = |current, rows| {
+            segment_rows(&segments, current, rows)
+                .into_iter()
+                .map(|row| {
+                    row.map_or(String::new(), |(i, segment)| {
+                        format!("{} {}", i, segment_label(segment))
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            labels(0, 5),
+            ["", "", "0 Work (25m)", "1 Short break (5m)", "2 Work (25m)"]
+        );
+        // At the long break, the next cycle starts below it.
+        assert_eq!(
+            labels(3, 5),
+            [
+                "1 Short break (5m)",
+                "2 Work (25m)",
+                "3 Long break (15m)",
+                "4 Work (25m)",
+                "5 Short break (5m)",
+            ]
+        );
+        assert_eq!(
+            labels(4, 4),
+            [
+                "3 Long break (15m)",
+                "4 Work (25m)",
+                "5 Short break (5m)",
+                "6 Work (25m)"
+            ]
+        );
+        assert!(labels(2, 0).is_empty());
+    }
+
+    #[test]
+    fn test_big_digits() {
+        assert_eq!(
+            big_digits("25:00"),
+            [
+                "█████ █████   █████ █████",
+                "    █ █       █   █ █   █",
+                "    █ █     █ █   █ █   █",
+                "█████ █████   █   █ █   █",
+                "█         █ █ █   █ █   █",
+                "█         █   █   █ █   █",
+                "█████ █████   █████ █████",
+            ]
+        );
+        let rows = big_digits("1:2x");
+        assert_eq!(rows.len(), BIG_DIGIT_ROWS);
+        assert!(rows
+            .iter()
+            .all(|row| row.chars().count() == 5 + 1 + 1 + 1 + 5 + 1 + 5));
+        // The digits only go above the usual layout if there is room for them.
+        let segments = [PomoSegment::Work(25, None)];
+        let progress = SessionProgress {
+            segments_list: &segments,
+            i_segment: 0,
+            outcomes: &[],
+        };
+        let options = DisplayOptions {
+            big_digits: true,
+            ..DisplayOptions::new(Theme::default(), Keymap::default(), false, false)
+        };
+        let state = CountdownState::new(Duration::from_secs(25 * 60));
+        for (width, shown) in [(60, true), (20, false)] {
+            let backend = ratatui::backend::TestBackend::new(width, 30);
+            let mut terminal = ratatui::Terminal::new(backend).unwrap();
+            terminal
+                .draw(|f| {
+                    render_countdown(
+                        f,
+                        progress,
+                        state,
+                        None,
+                        PauseState::Running,
+                        false,
+                        &options,
+                    );
+                })
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            assert_eq!((0..width).any(|x| buffer.get(x, 0).symbol == "█"), shown);
+        }
+    }
+
+    #[test]
+    fn test_gauge_color() {
+        let (work, break_) = (true, false);
+        assert_eq!(gauge_color(work, 1.0), Color::Green);
+        assert_eq!(gauge_color(work, 0.5), Color::Yellow);
+        assert_eq!(gauge_color(work, 0.2), Color::Yellow);
+        assert_eq!(gauge_color(work, 0.1), Color::Red);
+        assert_eq!(gauge_color(break_, 0.9), Color::Blue);
+        assert_eq!(gauge_color(break_, 0.0), Color::Green);
+    }
+
+    #[test]
+    fn test_progress_ratio() {
+        let total = Duration::from_secs(60);
+        assert_eq!(progress_ratio(total, total), 0.0);
+        assert_eq!(progress_ratio(total, Duration::from_secs(15)), 0.75);
+        assert_eq!(progress_ratio(total, Duration::ZERO), 1.0);
+        assert_eq!(progress_ratio(Duration::ZERO, Duration::ZERO), 1.0);
+        assert_eq!(progress_ratio(total, Duration::from_secs(90)), 0.0);
+    }
+
+    #[test]
+    fn test_zero_length_segments() {
+        assert!(PomoCommand::try_parse_from(["pomo", "--time", "0"]).is_err());
+        assert!(PomoCommand::try_parse_from(["pomo", "--time", "90s"]).is_err());
+        let command = PomoCommand::parse_from(["pomo", "--time", "1h30m", "--break", "10m"]);
+        assert_eq!((command.time, command.break_), (Some(90), Some(10)));
+        assert!(
+            check_segments(&[PomoSegment::Work(25, None), PomoSegment::ShortBreak(0)]).is_err()
+        );
+        assert!(check_segments(&[PomoSegment::Work(100_000, None)]).is_ok());
+    }
+
+    #[test]
+    fn test_format_end_times() {
+        use chrono::TimeZone;
+        let now = Local.with_ymd_and_hms(2024, 3, 1, 13, 40, 20).unwrap();
+        assert_eq!(
+            format_end_times(
+                now,
+                Duration::from_secs(25 * 60),
+                Duration::from_secs(2 * 3600)
+            ),
+            "Ends at 14:05   Cycle ends at 15:40"
+        );
+        assert_eq!(
+            format_end_times(now, Duration::ZERO, Duration::from_secs(11 * 3600)),
+            "Ends at 13:40   Cycle ends at 00:40"
+        );
+    }
+
+    #[test]
+    fn test_gauge_label() {
+        let total = Duration::from_secs(25 * 60);
+        let remaining = Duration::from_secs(20 * 60 + 5);
+        let mut label = GaugeLabel::default();
+        let mut texts = vec![];
+        for _ in 0..4 {
+            texts.push(label.text(total, remaining, Precision::Seconds));
+            label = label.next();
+        }
+        assert_eq!(texts, ["20:05", "04:55 elapsed", "20%", "20:05"]);
+        assert_eq!(
+            GaugeLabel::Percent.text(Duration::ZERO, Duration::ZERO, Precision::Seconds),
+            "100%"
+        );
+        let remaining = Duration::from_millis(20 * 60_000 + 29_950);
+        let text = |label: GaugeLabel, precision| label.text(total, remaining, precision);
+        assert_eq!(
+            text(GaugeLabel::Remaining, Precision::Deciseconds),
+            "20:29.9"
+        );
+        assert_eq!(
+            text(GaugeLabel::Elapsed, Precision::Deciseconds),
+            "04:30.0 elapsed"
+        );
+        assert_eq!(text(GaugeLabel::Remaining, Precision::Minutes), "20m");
+        assert_eq!(Precision::Minutes.format(Duration::from_secs(90)), "02m");
+    }
+
+    #[test]
+    fn test_build_segments() {
+        let describe = |segments: Vec<PomoSegment>| {
+            segments
+                .iter()
+                .map(|segment| format!("{} {}", segment, segment.duration().as_secs() / 60))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            describe(build_segments(25, 5, 15, 3)),
+            [
+                "Work 25",
+                "Short break 5",
+                "Work 25",
+                "Short break 5",
+                "Work 25",
+                "Long break 15"
+            ]
+        );
+        assert_eq!(
+            describe(build_segments(50, 10, 30, 1)),
+            ["Work 50", "Long break 30"]
+        );
+        assert_eq!(
+            describe(build_segments(50, 10, 30, 0)),
+            ["Work 50", "Long break 30"]
+        );
+        let command = PomoCommand::parse_from(["pomo"]);
+        assert_eq!(
+            describe(command.default_segments(&PomoConfig::default())),
+            describe(build_segments(
+                DEFAULT_TIME,
+                DEFAULT_BREAK,
+                DEFAULT_LONG_BREAK,
+                DEFAULT_N_POMOS
+            ))
+        );
+    }
+
+    #[test]
+    fn test_no_long_break() {
+        let command = PomoCommand::parse_from(["pomo", "--no-long-break", "--time", "50"]);
+        let names =
+            |segments: &[PomoSegment]| segments.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let segments = command.default_segments(&PomoConfig::default());
+        assert_eq!(names(&segments), ["Work", "Short break"]);
+        assert_eq!(segments[0].duration(), Duration::from_secs(50 * 60));
+        let tasks = vec![PomoSegment::Work(25, None), PomoSegment::Work(25, None)];
+        let segments = command.task_segments(tasks, &PomoConfig::default());
+        assert_eq!(
+            names(&segments),
+            ["Work", "Short break", "Work", "Short break"]
+        );
+        assert!(PomoCommand::try_parse_from(["pomo", "--no-long-break", "-l", "20"]).is_err());
+    }
+
+    #[test]
+    fn test_warnings() {
+        let now =
+            NaiveDateTime::parse_from_str("2024-03-01 16:30:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let warnings = |args: &[&str], config: &PomoConfig| {
+            let command = PomoCommand::parse_from(["pomo"].iter().chain(args).copied());
+            command.warnings(config, &command.default_segments(config), now)
+        };
+        assert!(warnings(&["--check"], &PomoConfig::default()).is_empty());
+        assert_eq!(
+            warnings(&["--repeat", "0"], &PomoConfig::default()),
+            ["--repeat 0 runs no cycles, so no segment would ever start"]
+        );
+        // The first long break is 3 × 25 + 2 × 5 = 85 minutes in, at 17:55.
+        assert_eq!(
+            warnings(&["--until", "17:30"], &PomoConfig::default()),
+            [
+                "--until 17:30 comes before the first long break, 1h 25m into the cycle, so it's \
+                 never reached"
+            ]
+        );
+        assert!(warnings(&["--until", "18:00"], &PomoConfig::default()).is_empty());
+        assert_eq!(
+            warnings(&["--time", "300000000000000000"], &PomoConfig::default()),
+            ["a segment is 300000000000000000 minutes long, which is over a day"]
+        );
+        let config = PomoConfig {
+            long_break: Some(20),
+            ..PomoConfig::default()
+        };
+        assert_eq!(
+            warnings(&["--no-long-break", "--n-pomos", "3"], &config),
+            [
+                "--n-pomos does nothing with --no-long-break",
+                "`long_break` in the [pomo] section of the config file is unused with \
+                 --no-long-break"
+            ]
+        );
+        let command = PomoCommand::parse_from(["pomo", "--sequence", "w25,b5", "-t", "50"]);
+        let segments = parse_sequence("w25,b5").unwrap();
+        assert_eq!(
+            command.warnings(&PomoConfig::default(), &segments, now),
+            ["ignoring --time in favor of --sequence"]
+        );
+        assert_eq!(
+            check_summary(2),
+            "Config and flags are usable, with 2 warnings"
+        );
+        assert!(PomoCommand::try_parse_from(["pomo", "--check", "--print-config"]).is_err());
+    }
+
+    #[test]
+    fn test_checked_config_renders() {
+        // `--check` only warns about a segment this long, so running it has to draw without
+        // overflowing.
+        let command = PomoCommand::parse_from(["pomo", "--time", "300000000000000000"]);
+        let segments = command.default_segments(&PomoConfig::default());
+        let options = command.display_options(Theme::default(), Keymap::default());
+        let progress = SessionProgress {
+            segments_list: &segments,
+            i_segment: 0,
+            outcomes: &[],
+        };
+        let state = CountdownState::new(segments[0].duration());
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+        terminal
+            .draw(|f| {
+                render_countdown(
+                    f,
+                    progress,
+                    state,
+                    None,
+                    PauseState::Running,
+                    false,
+                    &options,
+                );
+            })
+            .unwrap();
+        assert!(crate::tui::drawn_lines(&terminal)
+            .iter()
+            .any(|line| line.contains("300000000000000000:00")));
+    }
+
+    #[test]
+    fn test_alert_options() {
+        let command = PomoCommand::parse_from(["pomo"]);
+        assert_eq!((command.volume, command.alert_repeat), (100, 1));
+        assert!(command.alerts().unwrap().work.is_none());
+        let command = PomoCommand::parse_from(["pomo", "--volume", "0", "--alert-repeat", "3"]);
+        assert_eq!((command.volume, command.alert_repeat), (0, 3));
+        assert!(PomoCommand::try_parse_from(["pomo", "--volume", "101"]).is_err());
+        assert!(PomoCommand::try_parse_from(["pomo", "--volume", "-5"]).is_err());
+        assert!(PomoCommand::try_parse_from(["pomo", "--alert-repeat", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_task_segments() {
+        let command = PomoCommand::parse_from(["pomo", "--n-pomos", "2", "--break", "3"]);
+        let tasks = (1..=3)
+            .map(|i| PomoSegment::Work(25, Some(format!("Task {}", i))))
+            .collect();
+        let segments = command.task_segments(tasks, &PomoConfig::default());
+        let names: Vec<&str> = segments.iter().map(Into::into).collect();
+        assert_eq!(
+            names,
+            [
+                "Task 1",
+                "Short break",
+                "Task 2",
+                "Long break",
+                "Task 3",
+                "Long break"
+            ]
+        );
+        assert_eq!(segments[1].duration(), Duration::from_secs(3 * 60));
+    }
+
+    #[test]
+    fn test_print_config() {
+        let command = PomoCommand::parse_from(["pomo", "--time", "50"]);
+        let file =
+            toml::from_str::<Config>("[pomo]\nlong_break = 20\n\n[pomo.keys]\npause = \"p\"\n")
+                .unwrap();
+        let keys = load_keys(&file).unwrap();
+        let resolved = command.resolved_config(&file.pomo, &keys);
+        let segments = command.default_segments(&file.pomo);
+        let printed = format_config(&resolved, &segments, false).unwrap();
+        assert!(printed.starts_with("# 6 segments a cycle, 3h in all\n[pomo]\ntime = 50\n"));
+        // Loading what was printed gives the same session back.
+        let reloaded = toml::from_str::<Config>(&printed).unwrap();
+        assert_eq!(reloaded.pomo.time, Some(50));
+        assert_eq!(reloaded.pomo.long_break, Some(20));
+        assert_eq!(reloaded.pomo.n_pomos, Some(DEFAULT_N_POMOS));
+        let reloaded_keys = load_keys(&reloaded).unwrap();
+        for input in [PomoInput::Pause, PomoInput::Quit, PomoInput::Previous] {
+            assert_eq!(reloaded_keys.describe(input), keys.describe(input));
+        }
+    }
+
+    #[test]
+    fn test_format_schedule() {
+        let segments = vec![
+            PomoSegment::Work(25, None),
+            PomoSegment::ShortBreak(5),
+            PomoSegment::Work(25, None),
+            PomoSegment::ShortBreak(5),
+            PomoSegment::Work(25, None),
+            PomoSegment::LongBreak(15),
+        ];
+        assert_eq!(
+            format_schedule(&segments),
+            "Work 25m, Short break 5m, Work 25m, Short break 5m, Work 25m, Long break 15m\n\
+             Total: 1h 40m"
+        );
+        assert_eq!(format_minutes(45), "45m");
+        assert_eq!(format_minutes(120), "2h");
+    }
+
+    #[test]
+    fn test_format_plain() {
+        let remaining = Duration::from_secs(24 * 60 + 31);
+        assert_eq!(
+            format_plain(&PomoSegment::Work(25, None), remaining, false),
+            "[Work] 24:31 remaining"
+        );
+        assert_eq!(
+            format_plain(
+                &PomoSegment::LongBreak(90),
+                Duration::from_secs(90 * 60),
+                true
+            ),
+            "[Long break] 90:00 remaining (paused)"
+        );
+    }
+}
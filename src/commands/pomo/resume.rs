@@ -0,0 +1,89 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Where a quit session left off, for `--resume` to pick up from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedState {
+    pub saved_at: DateTime<Local>,
+    /// The `--show-schedule` description of the cycle, so that a session with different segments
+    /// doesn't resume at a position that means something else
+    pub schedule: String,
+    /// Index of the segment quit during, counting from the start of the session
+    pub segment_index: usize,
+    pub remaining_secs: u64,
+}
+
+/// The state lives next to the history log, at `<data dir>/kit/pomo_state.json`.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("kit").join("pomo_state.json"))
+}
+
+pub fn save(path: &Path, state: &SavedState) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string(state)?)
+}
+
+/// The saved state at `path`, if there is one. A file that can't be read or parsed is reported
+/// and otherwise treated as missing, so that a bad file never stops a session from starting.
+pub fn load(path: &Path) -> Option<SavedState> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            log::warn!("cannot read {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    match serde_json::from_str(&contents) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            log::warn!(
+                "ignoring corrupt session state in {}: {}",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+pub fn clear(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_load_clear() {
+        let dir = std::env::temp_dir().join(format!("kit-resume-test-{}", std::process::id()));
+        let path = dir.join("pomo_state.json");
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(load(&path), None);
+        let state = SavedState {
+            saved_at: Local::now(),
+            schedule: "Work 25m, Long break 15m\nTotal: 40m".to_string(),
+            segment_index: 3,
+            remaining_secs: 600,
+        };
+        save(&path, &state).unwrap();
+        assert_eq!(load(&path), Some(state));
+        clear(&path).unwrap();
+        assert_eq!(load(&path), None);
+        clear(&path).unwrap();
+        fs::write(&path, "{\"segment_index\": 3").unwrap();
+        assert_eq!(load(&path), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
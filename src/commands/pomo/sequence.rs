@@ -0,0 +1,282 @@
+use std::fmt;
+
+use super::PomoSegment;
+use crate::duration;
+
+/// What went wrong with a `--sequence` spec, and where.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    message: String,
+    /// Byte offset into the spec
+    position: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse a spec like `w25,b5,w25,lb20` or `3x(w25,b5),lb20` into the segments it lists. `w`, `b`
+/// and `lb` are work, short break and long break, each followed by a length in minutes or a
+/// duration like `1h30m`, and `Nx(...)` repeats the segments in parentheses `N` times. Repeats
+/// may be nested, and whitespace between tokens is ignored.
+pub fn parse_sequence(spec: &str) -> Result<Vec<PomoSegment>, ParseError> {
+    let mut parser = Parser {
+        spec: spec.as_bytes(),
+        position: 0,
+    };
+    let segments = parser.sequence()?;
+    parser.skip_whitespace();
+    match parser.peek() {
+        None => Ok(segments),
+        Some(_) => Err(parser.error("expected `,` or the end of the sequence")),
+    }
+}
+
+/// The most segments a spec can expand to. Far more than any session needs, and small enough that
+/// a huge repeat count is a parse error instead of an allocation that can't succeed.
+const MAX_SEGMENTS: usize = 10_000;
+
+struct Parser<'a> {
+    spec: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            message: message.to_string(),
+            position: self.position,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.spec.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    /// Consume `token` if it comes next, after any whitespace.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.spec[self.position..].starts_with(token.as_bytes()) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), ParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", token)))
+        }
+    }
+
+    /// Items separated by commas.
+    fn sequence(&mut self) -> Result<Vec<PomoSegment>, ParseError> {
+        let mut segments = self.item()?;
+        while self.eat(",") {
+            self.skip_whitespace();
+            let start = self.position;
+            segments.extend(self.item()?);
+            if segments.len() > MAX_SEGMENTS {
+                self.position = start;
+                return Err(self.too_many());
+            }
+        }
+        Ok(segments)
+    }
+
+    fn too_many(&self) -> ParseError {
+        self.error(&format!(
+            "the sequence would have more than {} segments",
+            MAX_SEGMENTS
+        ))
+    }
+
+    /// A single segment, or a repeated group of them.
+    fn item(&mut self) -> Result<Vec<PomoSegment>, ParseError> {
+        self.skip_whitespace();
+        if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            let start = self.position;
+            let count = self.number()?;
+            self.expect("x")?;
+            self.expect("(")?;
+            let group = self.sequence()?;
+            self.expect(")")?;
+            if count == 0 {
+                self.position = start;
+                return Err(self.error("repeat count must be at least 1"));
+            }
+            let total = usize::try_from(count)
+                .ok()
+                .and_then(|count| count.checked_mul(group.len()));
+            if total.is_none_or(|total| total > MAX_SEGMENTS) {
+                self.position = start;
+                return Err(self.too_many());
+            }
+            return Ok(vec![group; count as usize].concat());
+        }
+        let segment: fn(u64) -> PomoSegment = if self.eat("lb") {
+            PomoSegment::LongBreak
+        } else if self.eat("w") {
+            |minutes| PomoSegment::Work(minutes, None)
+        } else if self.eat("b") {
+            PomoSegment::ShortBreak
+        } else {
+            return Err(
+                self.error("expected a segment (`w`, `b` or `lb`) or a repeat like `3x(...)`")
+            );
+        };
+        let start = self.position;
+        let minutes = self.length()?;
+        if minutes == 0 {
+            self.position = start;
+            return Err(self.error("segment length must be at least 1 minute"));
+        }
+        Ok(vec![segment(minutes)])
+    }
+
+    /// A segment length: minutes, or a duration like `1h30m` with no spaces in it.
+    fn length(&mut self) -> Result<u64, ParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || b"hms".contains(&c))
+        {
+            self.position += 1;
+        }
+        if self.position == start {
+            return Err(self.error("expected a number"));
+        }
+        // Only ASCII was consumed, so the slice is valid UTF-8.
+        let length = std::str::from_utf8(&self.spec[start..self.position]).unwrap();
+        duration::parse_minutes(length).map_err(|e| ParseError {
+            message: e.to_string(),
+            position: start,
+        })
+    }
+
+    fn number(&mut self) -> Result<u64, ParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        if self.position == start {
+            return Err(self.error("expected a number"));
+        }
+        // Only ASCII digits were consumed, so the slice is valid UTF-8.
+        let digits = std::str::from_utf8(&self.spec[start..self.position]).unwrap();
+        digits.parse().map_err(|_| ParseError {
+            message: "number is too large".to_string(),
+            position: start,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// `PomoSegment` has no `PartialEq`, so compare the kinds and lengths instead.
+    fn describe(segments: &[PomoSegment]) -> Vec<(&'static str, Duration)> {
+        segments.iter().map(|s| (s.kind(), s.duration())).collect()
+    }
+
+    #[test]
+    fn test_parse_flat() {
+        let segments = parse_sequence("w25,b5,w25, lb20").unwrap();
+        assert_eq!(
+            describe(&segments),
+            describe(&[
+                PomoSegment::Work(25, None),
+                PomoSegment::ShortBreak(5),
+                PomoSegment::Work(25, None),
+                PomoSegment::LongBreak(20),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_repeat() {
+        let segments = parse_sequence("3x(w25,b5),lb20").unwrap();
+        assert_eq!(segments.len(), 7);
+        assert_eq!(
+            describe(&segments[4..]),
+            describe(&[
+                PomoSegment::Work(25, None),
+                PomoSegment::ShortBreak(5),
+                PomoSegment::LongBreak(20),
+            ])
+        );
+        assert_eq!(parse_sequence("2x(w50, 2x(b5))").unwrap().len(), 6);
+        let segments = parse_sequence("w1h30m,b5m").unwrap();
+        assert_eq!(
+            describe(&segments),
+            describe(&[PomoSegment::Work(90, None), PomoSegment::ShortBreak(5)])
+        );
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let error = |spec| parse_sequence(spec).unwrap_err().to_string();
+        assert_eq!(
+            error(""),
+            "expected a segment (`w`, `b` or `lb`) or a repeat like `3x(...)` at column 1"
+        );
+        assert_eq!(error("w"), "expected a number at column 2");
+        assert_eq!(
+            error("w25,,b5"),
+            "expected a segment (`w`, `b` or `lb`) or a repeat like `3x(...)` at column 5"
+        );
+        assert_eq!(error("3(w25)"), "expected `x` at column 2");
+        assert_eq!(error("3x(w25"), "expected `)` at column 7");
+        assert_eq!(
+            error("w25)"),
+            "expected `,` or the end of the sequence at column 4"
+        );
+        assert_eq!(
+            error("0x(w5)"),
+            "repeat count must be at least 1 at column 1"
+        );
+        assert_eq!(
+            error("w0"),
+            "segment length must be at least 1 minute at column 2"
+        );
+        assert_eq!(
+            error("w99999999999999999999"),
+            "\"99999999999999999999\" is too long a duration at column 2"
+        );
+        assert_eq!(
+            error("w90s"),
+            "expected a whole number of minutes, like 25 or 1h30m, got \"90s\" at column 2"
+        );
+        assert_eq!(
+            error("w25, 99999999999x(w25)"),
+            "the sequence would have more than 10000 segments at column 6"
+        );
+        assert_eq!(
+            error("100x(100x(w25,b5))"),
+            "the sequence would have more than 10000 segments at column 1"
+        );
+        assert_eq!(
+            error("5000x(w25),5000x(b5),w25"),
+            "the sequence would have more than 10000 segments at column 22"
+        );
+        assert_eq!(parse_sequence("5000x(w25,b5)").unwrap().len(), MAX_SEGMENTS);
+    }
+}
@@ -0,0 +1,350 @@
+use std::{collections::BTreeMap, fmt, io, ops::ControlFlow, path::PathBuf, time::Duration};
+
+use chrono::{Datelike, Local, NaiveDate};
+use clap::Parser;
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    widgets, Frame,
+};
+
+use super::{
+    format_minutes,
+    history::{self, HistoryRecord, Outcome},
+    is_ctrl_c,
+};
+use crate::{
+    config::Config,
+    error::KitError,
+    theme::Theme,
+    tui::{run_app, App},
+    with_tui::{self, WithTui},
+};
+
+/// Width of the longest bar in the per-day chart
+const BAR_WIDTH: usize = 40;
+
+#[derive(Debug, Parser)]
+#[clap(about = "Show totals and a per-day chart of focused time from the pomo history log")]
+pub struct StatsCommand {
+    #[arg(
+        long,
+        help = "History log to read [default: ~/.local/share/kit/pomo_history.jsonl]",
+        value_name = "PATH"
+    )]
+    log: Option<PathBuf>,
+
+    #[arg(
+        long,
+        default_value_t = 7,
+        help = "Number of days to chart, ending today",
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..=3650)
+    )]
+    days: u64,
+
+    #[arg(
+        long,
+        help = "Show the minutes focused each day as a sparkline in a TUI, until 'q' is pressed"
+    )]
+    tui: bool,
+}
+
+impl StatsCommand {
+    pub async fn run(&self) -> Result<(), KitError> {
+        let Some(path) = self.log.clone().or_else(history::default_path) else {
+            println!("No history yet.");
+            return Ok(());
+        };
+        let records = match history::read(&path) {
+            Ok(records) => records,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(KitError::io_at("cannot read", &path, e)),
+        };
+        if records.is_empty() {
+            println!(
+                "No history yet. Work segments are logged to {} as they end.",
+                path.display()
+            );
+            return Ok(());
+        }
+        let stats = compute(&records, Local::now().date_naive(), self.days as usize);
+        if !self.tui {
+            print!("{}", stats);
+            return Ok(());
+        }
+        if !with_tui::is_terminal() {
+            return Err(KitError::Terminal(
+                "--tui needs a terminal; leave it off to print the stats instead".to_string(),
+            ));
+        }
+        let theme = Config::load()?.theme.with_ascii_only(false);
+        run_app(&mut StatsApp { stats, theme }).await
+    }
+}
+
+/// Totals over the history log, as of a given day.
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    today: Duration,
+    /// Since the Monday of the current week
+    this_week: Duration,
+    completed: usize,
+    /// Completed work segments per day, out of the days with anything logged at all
+    average_per_day: f64,
+    /// Focused time on each of the last few days, oldest first
+    by_day: Vec<(NaiveDate, Duration)>,
+}
+
+/// Focused time counts each work segment for as long as it ran, so segments that were skipped or
+/// quit partway through still count for the part that happened.
+pub fn compute(records: &[HistoryRecord], today: NaiveDate, n_days: usize) -> Stats {
+    let mut focused: BTreeMap<NaiveDate, Duration> = BTreeMap::new();
+    let mut completed = 0;
+    for record in records.iter().filter(|record| record.segment == "Work") {
+        *focused.entry(record.timestamp.date_naive()).or_default() +=
+            Duration::from_secs(record.elapsed_secs);
+        if record.outcome == Outcome::Completed {
+            completed += 1;
+        }
+    }
+    let on = |day: NaiveDate| focused.get(&day).copied().unwrap_or_default();
+    let week_start = today - chrono::Duration::days(today.weekday().num_days_from_monday().into());
+    Stats {
+        today: on(today),
+        this_week: focused.range(week_start..=today).map(|(_, d)| *d).sum(),
+        completed,
+        average_per_day: if focused.is_empty() {
+            0.0
+        } else {
+            completed as f64 / focused.len() as f64
+        },
+        by_day: (0..n_days as i64)
+            .rev()
+            .map(|days_ago| today - chrono::Duration::days(days_ago))
+            .map(|day| (day, on(day)))
+            .collect(),
+    }
+}
+
+impl Stats {
+    /// The totals, one a line, without the chart.
+    fn totals(&self) -> String {
+        let minutes = |duration: Duration| format_minutes(duration.as_secs() / 60);
+        format!(
+            "Today:     {}\nThis week: {}\nCompleted work segments: {} ({:.1} per active day)\n",
+            minutes(self.today),
+            minutes(self.this_week),
+            self.completed,
+            self.average_per_day
+        )
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes = |duration: Duration| format_minutes(duration.as_secs() / 60);
+        write!(f, "{}", self.totals())?;
+        let longest = self
+            .by_day
+            .iter()
+            .map(|(_, d)| *d)
+            .max()
+            .unwrap_or_default();
+        if !self.by_day.is_empty() {
+            writeln!(f)?;
+        }
+        for (day, focused) in &self.by_day {
+            let width = if longest.is_zero() {
+                0
+            } else {
+                (focused.as_secs_f64() / longest.as_secs_f64() * BAR_WIDTH as f64).round() as usize
+            };
+            writeln!(
+                f,
+                "{} {:<width$} {}",
+                day.format("%a %m-%d"),
+                "#".repeat(width),
+                minutes(*focused),
+                width = BAR_WIDTH
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The stats with the chart drawn as a sparkline, one column a day, which takes as many days as
+/// `--days` asks for to fill out.
+struct StatsApp {
+    stats: Stats,
+    theme: Theme,
+}
+
+impl WithTui for StatsApp {}
+
+impl App for StatsApp {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>) -> Result<(), KitError> {
+        let chunks = Layout::default()
+            .margin(1)
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(5),
+                Constraint::Length(8),
+                Constraint::Min(0),
+            ])
+            .split(f.size());
+        let totals = widgets::Paragraph::new(self.stats.totals())
+            .block(self.theme.block().title("Focused time"));
+        let minutes: Vec<u64> = self
+            .stats
+            .by_day
+            .iter()
+            .map(|(_, focused)| focused.as_secs() / 60)
+            .collect();
+        let title = match (self.stats.by_day.first(), self.stats.by_day.last()) {
+            (Some((first, _)), Some((last, _))) => format!(
+                "Minutes a day, {} to {}",
+                first.format("%a %m-%d"),
+                last.format("%a %m-%d")
+            ),
+            _ => "Minutes a day".to_string(),
+        };
+        let sparkline = widgets::Sparkline::default()
+            .block(self.theme.block().title(title))
+            .data(&minutes)
+            .style(Style::default().fg(self.theme.gauge.unwrap_or(Color::Green)));
+        f.render_widget(totals, chunks[0]);
+        f.render_widget(sparkline, chunks[1]);
+        f.render_widget(widgets::Paragraph::new("q: quit"), chunks[2]);
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<ControlFlow<()>, KitError> {
+        match event {
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('q') | KeyCode::Esc,
+                ..
+            }) => Ok(ControlFlow::Break(())),
+            _ if is_ctrl_c(event) => Ok(ControlFlow::Break(())),
+            _ => Ok(ControlFlow::Continue(())),
+        }
+    }
+
+    fn ascii_only(&self) -> bool {
+        self.theme.ascii_only
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn record(day: u32, hour: u32, elapsed_mins: u64, outcome: Outcome) -> HistoryRecord {
+        HistoryRecord {
+            timestamp: Local.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap(),
+            segment: "Work".to_string(),
+            task: None,
+            planned_secs: 25 * 60,
+            elapsed_secs: elapsed_mins * 60,
+            paused_secs: 0,
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_compute() {
+        // 2024-03-06 is a Wednesday, so the week started on the 4th.
+        let today = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        let records = vec![
+            record(1, 9, 25, Outcome::Completed),
+            record(4, 9, 25, Outcome::Completed),
+            record(4, 10, 25, Outcome::Completed),
+            record(6, 9, 25, Outcome::Completed),
+            record(6, 10, 10, Outcome::Quit),
+            record(7, 9, 25, Outcome::Completed),
+        ];
+        let stats = compute(&records, today, 3);
+        assert_eq!(stats.today, Duration::from_secs(35 * 60));
+        assert_eq!(stats.this_week, Duration::from_secs(85 * 60));
+        assert_eq!(stats.completed, 5);
+        assert_eq!(stats.average_per_day, 5.0 / 4.0);
+        assert_eq!(
+            stats.by_day,
+            vec![
+                (
+                    NaiveDate::from_ymd_opt(2024, 3, 4).unwrap(),
+                    Duration::from_secs(50 * 60)
+                ),
+                (NaiveDate::from_ymd_opt(2024, 3, 5).unwrap(), Duration::ZERO),
+                (today, Duration::from_secs(35 * 60)),
+            ]
+        );
+        let chart: Vec<String> = stats
+            .to_string()
+            .lines()
+            .skip(4)
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect();
+        assert_eq!(
+            chart,
+            vec![
+                format!("Mon 03-04 {} 50m", "#".repeat(40)),
+                "Tue 03-05 0m".to_string(),
+                format!("Wed 03-06 {} 35m", "#".repeat(28)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compute_empty() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        let stats = compute(&[], today, 0);
+        assert_eq!(stats.completed, 0);
+        assert_eq!(stats.average_per_day, 0.0);
+        assert!(stats.by_day.is_empty());
+        assert!(StatsCommand::try_parse_from(["stats", "--days", "100000000"]).is_err());
+        assert!(StatsCommand::try_parse_from(["stats", "--days", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_stats_app_sparkline() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        let records = vec![
+            record(4, 9, 50, Outcome::Completed),
+            record(6, 9, 25, Outcome::Completed),
+        ];
+        let mut app = StatsApp {
+            stats: compute(&records, today, 3),
+            theme: Theme::default(),
+        };
+        let backend = ratatui::backend::TestBackend::new(40, 16);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|f| app.draw(f).unwrap()).unwrap();
+        let lines = crate::tui::drawn_lines(&terminal);
+        assert!(lines[1].contains("Focused time"));
+        assert!(lines[2].contains("Today:     25m"));
+        let top = lines
+            .iter()
+            .position(|line| line.contains("Minutes a day"))
+            .unwrap();
+        // A column a day inside the border: the 4th in full, nothing on the 5th, and the 6th
+        // half as high.
+        let column = |x: usize| -> String {
+            lines[top + 1..top + 7]
+                .iter()
+                .map(|line| line.chars().nth(x).unwrap())
+                .collect()
+        };
+        assert_eq!(column(2), "██████");
+        assert_eq!(column(3), "      ");
+        assert_eq!(column(4).trim(), "███");
+        assert!(app
+            .handle_event(Event::Key(KeyEvent::from(KeyCode::Char('q'))))
+            .unwrap()
+            .is_break());
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+use super::PomoSegment;
+use crate::{duration, error::KitError};
+
+/// Read a `--tasks` file, or stdin if the path is `-`, into one labeled work segment per task.
+pub fn read_tasks(path: &Path) -> Result<Vec<PomoSegment>, KitError> {
+    let contents = if path == Path::new("-") {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        fs::read_to_string(path).map_err(|e| KitError::io_at("cannot read", path, e))?
+    };
+    let tasks = parse_tasks(&contents).map_err(|e| {
+        KitError::InvalidArgument(format!("invalid --tasks file {}: {}", path.display(), e))
+    })?;
+    if tasks.is_empty() {
+        return Err(KitError::InvalidArgument(format!(
+            "no tasks in {}",
+            path.display()
+        )));
+    }
+    Ok(tasks)
+}
+
+/// Parse lines like `25 Write the report` or `1h30m Review PRs`: a length in minutes or a
+/// duration, then the task's name. Blank lines and lines starting with `#` are skipped.
+pub fn parse_tasks(contents: &str) -> Result<Vec<PomoSegment>, String> {
+    contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(n, line)| {
+            let (minutes, name) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let minutes = match duration::parse_minutes(minutes) {
+                Ok(0) => return Err(format!("line {}: task length must be at least 1 minute", n)),
+                Ok(minutes) => minutes,
+                Err(e) => return Err(format!("line {}: {}", n, e)),
+            };
+            let name = name.trim();
+            let label = (!name.is_empty()).then(|| name.to_string());
+            Ok(PomoSegment::Work(minutes, label))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_tasks() {
+        let tasks =
+            parse_tasks("# today\n25 Write the report\n\n  50m\tReview PRs \n15\n").unwrap();
+        let described: Vec<_> = tasks
+            .iter()
+            .map(|t| (t.to_string(), t.duration()))
+            .collect();
+        assert_eq!(
+            described,
+            [
+                ("Write the report".to_string(), Duration::from_secs(25 * 60)),
+                ("Review PRs".to_string(), Duration::from_secs(50 * 60)),
+                ("Work".to_string(), Duration::from_secs(15 * 60)),
+            ]
+        );
+        assert_eq!(
+            parse_tasks("25 Write\nsoon Review").unwrap_err(),
+            "line 2: expected a duration like 10m, 1h30m or 90s, got \"soon\""
+        );
+        assert_eq!(
+            parse_tasks("0 Nothing").unwrap_err(),
+            "line 1: task length must be at least 1 minute"
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use std::{ops::ControlFlow, path::PathBuf, time::Duration};
+
+use clap::Parser;
+use crossterm::event::Event;
+use futures::FutureExt;
+use itertools::Itertools;
+use notify_rust::Notification;
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout},
+    Frame,
+};
+
+use super::{
+    format_hms, format_mmss, gauge_color, help_table, load_keys, progress_gauge, progress_ratio,
+    Countdown, CountdownState, DisplayOptions, PomoInput, HELP_WIDTH, TICK_RATE,
+};
+use crate::{
+    config::Config,
+    duration::parse_duration,
+    error::KitError,
+    sound::{self, Sound},
+    theme::{self, Theme},
+    tui::{run_app, App},
+    with_tui::WithTui,
+};
+
+#[derive(Debug, Parser)]
+#[clap(about = "Count down once and signal when the time is up. Press 'h' for help while running.")]
+pub struct TimerCommand {
+    #[arg(
+        help = "How long to count down, like `10m`, `1h30m` or `90s`; a bare number is minutes",
+        value_parser = parse_countdown
+    )]
+    duration: Duration,
+
+    #[arg(long, help = "Show a desktop notification when the time is up")]
+    notify: bool,
+
+    #[arg(
+        long,
+        help = "Sound file to play when the time is up (needs the `sound` feature)",
+        value_name = "PATH"
+    )]
+    sound: Option<PathBuf>,
+
+    #[arg(long, help = "Ring the terminal bell when the time is up")]
+    bell: bool,
+
+    #[arg(
+        long,
+        help = "Draw the TUI without colors (also set by the NO_COLOR variable)"
+    )]
+    no_color: bool,
+
+    #[arg(
+        long,
+        help = "Color the TUI with a built-in theme instead of the config file's: `default`, \
+                `solarized`, `mono` or `high-contrast`",
+        value_name = "NAME",
+        value_parser = theme::parse_preset
+    )]
+    theme: Option<Theme>,
+
+    #[arg(
+        long,
+        help = "Draw the TUI with plain ASCII (also set by a TERM of `dumb` or `vt*`)"
+    )]
+    ascii_only: bool,
+}
+
+impl TimerCommand {
+    pub async fn run(&self) -> Result<(), KitError> {
+        let config = Config::load()?;
+        let options = DisplayOptions::new(
+            config
+                .theme
+                .overridden_by(self.theme)
+                .with_ascii_only(self.ascii_only),
+            load_keys(&config)?,
+            self.no_color,
+            false,
+        );
+        let sound = self.sound.as_deref().map(Sound::load).transpose()?;
+        log::info!("starting a {} timer", format_hms(self.duration));
+        let mut timer = Timer {
+            countdown: Countdown::start(self.duration, false, TICK_RATE),
+            is_paused: false,
+            show_help: false,
+            finished: false,
+            options,
+        };
+        run_app(&mut timer).await?;
+        if !timer.finished {
+            return Ok(());
+        }
+        println!("Time's up: {}", format_hms(self.duration));
+        if self.bell {
+            sound::bell()?;
+        }
+        if self.notify {
+            let shown = Notification::new()
+                .summary("Time's up")
+                .body(&format!("{} timer done", format_hms(self.duration)))
+                .show();
+            if let Err(e) = shown {
+                log::debug!("failed to show notification: {}", e);
+            }
+        }
+        // The process is about to exit, so play the sound here rather than in the background.
+        if let Some(sound) = sound {
+            if let Err(e) = sound.play() {
+                log::warn!("failed to play sound: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single countdown with nothing after it.
+struct Timer {
+    countdown: Countdown,
+    is_paused: bool,
+    show_help: bool,
+    /// Whether the countdown ran out, as opposed to the user quitting
+    finished: bool,
+    options: DisplayOptions,
+}
+
+impl WithTui for Timer {}
+
+impl App for Timer {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>) -> Result<(), KitError> {
+        let state = self.countdown.state();
+        render_timer(
+            f,
+            self.countdown.duration,
+            state,
+            self.is_paused,
+            self.show_help,
+            &self.options,
+        );
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<ControlFlow<()>, KitError> {
+        match self.options.keys.input(event) {
+            Some(PomoInput::Help) => self.show_help = !self.show_help,
+            Some(PomoInput::Pause) => {
+                self.is_paused = !self.is_paused;
+                self.countdown.tx_paused.send(self.is_paused)?;
+            }
+            Some(PomoInput::Quit) => {
+                self.countdown.tx_cancel.try_send(())?;
+                return Ok(ControlFlow::Break(()));
+            }
+            Some(
+                PomoInput::Skip
+                | PomoInput::Previous
+                | PomoInput::Label
+                | PomoInput::EndTimes
+                | PomoInput::Resize,
+            )
+            | None => {}
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    fn tick(&mut self) -> Result<ControlFlow<()>, KitError> {
+        match (&mut self.countdown.handle).now_or_never() {
+            Some(finished) => {
+                finished??;
+                log::info!("timer done");
+                self.finished = true;
+                Ok(ControlFlow::Break(()))
+            }
+            None => Ok(ControlFlow::Continue(())),
+        }
+    }
+
+    fn tick_rate(&self) -> Option<Duration> {
+        Some(TICK_RATE)
+    }
+
+    fn ascii_only(&self) -> bool {
+        self.options.theme.ascii_only
+    }
+
+    fn title(&self) -> Option<String> {
+        Some(format!(
+            "{} - {}",
+            std::env::args().join(" "),
+            format_mmss(self.countdown.remaining())
+        ))
+    }
+}
+
+/// Draw the gauge for a timer of `total`, with the help below it if it's toggled on.
+fn render_timer<B: Backend>(
+    f: &mut Frame<B>,
+    total: Duration,
+    state: CountdownState,
+    is_paused: bool,
+    show_help: bool,
+    options: &DisplayOptions,
+) {
+    let remaining = state.remaining;
+    let color = gauge_color(true, 1.0 - progress_ratio(total, remaining));
+    let title = format!("Timer: {}", format_hms(total));
+    let gauge = progress_gauge(&options.theme, &title, total, remaining, is_paused, color);
+    let vertical_margin = f.size().height.saturating_sub(8).div_euclid(4);
+    let chunks = Layout::default()
+        .horizontal_margin(4)
+        .vertical_margin(vertical_margin)
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(5),
+            Constraint::Min(0),
+        ])
+        .split(f.size());
+    f.render_widget(gauge, chunks[0]);
+    if show_help {
+        let help_area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(HELP_WIDTH), Constraint::Min(0)])
+            .split(chunks[1]);
+        f.render_widget(
+            help_table(&[(PomoInput::Pause, "Pause timer")], options),
+            help_area[0],
+        );
+    }
+}
+
+/// A `parse_duration` duration to count down, which has to be more than zero.
+fn parse_countdown(s: &str) -> Result<Duration, String> {
+    match parse_duration(s) {
+        Ok(duration) if duration.is_zero() => {
+            Err("the duration must be more than zero".to_string())
+        }
+        result => result.map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_countdown() {
+        assert_eq!(parse_countdown("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_countdown("25"), Ok(Duration::from_secs(1500)));
+        for zero in ["0", "0s", "0h0m"] {
+            assert_eq!(
+                parse_countdown(zero),
+                Err("the duration must be more than zero".to_string())
+            );
+        }
+        assert_eq!(
+            parse_countdown("1.5h"),
+            Err("expected a duration like 10m, 1h30m or 90s, got \"1.5h\"".to_string())
+        );
+    }
+}
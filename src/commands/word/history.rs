@@ -0,0 +1,141 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// How many patterns are kept from one run to the next
+const MAX_SAVED: usize = 500;
+
+/// Patterns entered in the TUI, oldest first, and where `^P` and `^N` have stepped to in them.
+#[derive(Debug, Default)]
+pub struct History {
+    entries: Vec<String>,
+    /// Index of the entry recalled into the input, or `None` while typing a pattern of one's own
+    position: Option<usize>,
+    /// What was typed before stepping back, given back on stepping forward past the newest entry
+    draft: String,
+}
+
+impl History {
+    pub fn new(entries: Vec<String>) -> Self {
+        Self {
+            entries,
+            ..Self::default()
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Add `pattern` as the newest entry, unless it's empty or already the newest.
+    pub fn push(&mut self, pattern: &str) {
+        self.position = None;
+        if !pattern.is_empty() && self.entries.last().is_none_or(|last| last != pattern) {
+            self.entries.push(pattern.to_string());
+        }
+    }
+
+    /// Stop stepping through the history, as when a recalled pattern is edited. The edited
+    /// pattern becomes the draft the next time `previous` is called.
+    pub fn stop(&mut self) {
+        self.position = None;
+    }
+
+    /// The entry before the one last recalled, or the newest if none was. `current` is kept as
+    /// the draft when stepping in from it.
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let position = match self.position {
+            Some(0) => return None,
+            Some(i) => i - 1,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len().checked_sub(1)?
+            }
+        };
+        self.position = Some(position);
+        Some(&self.entries[position])
+    }
+
+    /// The entry after the one last recalled, or the draft after the newest. Nothing while no
+    /// entry is recalled.
+    pub fn next(&mut self) -> Option<&str> {
+        let position = self.position? + 1;
+        if position < self.entries.len() {
+            self.position = Some(position);
+            Some(&self.entries[position])
+        } else {
+            self.position = None;
+            Some(&self.draft)
+        }
+    }
+}
+
+/// The history lives at `<data dir>/kit/word_history`, one pattern a line.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("kit").join("word_history"))
+}
+
+/// The patterns saved at `path`. A missing file is an empty history, and one that can't be read
+/// is reported and treated the same way.
+pub fn load(path: &Path) -> Vec<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => contents.lines().map(str::to_string).collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+        Err(e) => {
+            log::warn!("cannot read {}: {}", path.display(), e);
+            vec![]
+        }
+    }
+}
+
+/// Save the newest `MAX_SAVED` entries to `path`.
+pub fn save(path: &Path, entries: &[String]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let kept = &entries[entries.len().saturating_sub(MAX_SAVED)..];
+    fs::write(
+        path,
+        kept.iter()
+            .map(|entry| format!("{}\n", entry))
+            .collect::<String>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history() {
+        let mut history = History::new(vec!["^ca".to_string()]);
+        history.push("^cat");
+        history.push("^cat");
+        history.push("");
+        assert_eq!(history.entries(), ["^ca", "^cat"]);
+        assert_eq!(history.next(), None);
+        assert_eq!(history.previous("^do"), Some("^cat"));
+        assert_eq!(history.previous("^cat"), Some("^ca"));
+        assert_eq!(history.previous("^ca"), None);
+        assert_eq!(history.next(), Some("^cat"));
+        assert_eq!(history.next(), Some("^do"));
+        assert_eq!(history.next(), None);
+        assert_eq!(history.previous("^do"), Some("^cat"));
+        history.stop();
+        assert_eq!(history.previous("^cats"), Some("^cat"));
+        assert_eq!(history.next(), Some("^cats"));
+    }
+
+    #[test]
+    fn test_save_load() {
+        let dir = std::env::temp_dir().join(format!("kit-word-history-{}", std::process::id()));
+        let path = dir.join("word_history");
+        let _ = fs::remove_dir_all(&dir);
+        assert!(load(&path).is_empty());
+        let entries: Vec<String> = (0..MAX_SAVED + 2).map(|i| format!("^{}", i)).collect();
+        save(&path, &entries).unwrap();
+        assert_eq!(load(&path), entries[2..]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,2922 @@
+mod history;
+mod stats;
+mod wordle;
+
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs,
+    io::{self, stdin, stdout, BufRead, IsTerminal, Write},
+    ops::{ControlFlow, Range},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+use clap::{Parser, Subcommand, ValueEnum};
+use crossterm::{
+    event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    style::Stylize,
+    terminal,
+};
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use ratatui::{
+    backend::Backend,
+    layout,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets, Frame,
+};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{
+    config::Config,
+    csv::csv_field,
+    error::KitError,
+    theme::{self, Theme},
+    tui::{run_app, App},
+    with_tui::{self, WithTui},
+};
+use history::History;
+use wordle::WordleCommand;
+
+const WORDS: &str = include_str!("../../../data/words.txt");
+const COMMON_WORDS: &str = include_str!("../../../data/common.txt");
+const FIVE_LETTER_WORDS: &str = include_str!("../../../data/five_letters.txt");
+
+#[derive(Debug, Parser)]
+#[clap(about = "Search for English words matching a regex input.")]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct WordCommand {
+    #[command(subcommand)]
+    subcommand: Option<WordSubcommand>,
+
+    #[arg(
+        short,
+        long,
+        help = "Launch an interactive TUI to input regexes; press '?' on an empty input for help"
+    )]
+    interactive: bool,
+
+    #[arg(
+        long,
+        help = "Newline-delimited word list to search instead of the built-in dictionary; repeat \
+                to combine several",
+        value_name = "PATH"
+    )]
+    dict: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Builtin::All,
+        help = "Which built-in dictionary to search; see --list-dicts. Can't be given with \
+                --dict or --stdin, which replace the built-in dictionaries altogether",
+        value_name = "NAME",
+        conflicts_with_all = ["dict", "stdin"]
+    )]
+    builtin: Builtin,
+
+    #[arg(
+        long,
+        help = "List the built-in dictionaries and how many words are in each, then exit",
+        exclusive = true
+    )]
+    list_dicts: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = DictMode::Union,
+        help = "How to combine more than one --dict"
+    )]
+    dict_mode: DictMode,
+
+    #[arg(
+        long,
+        help = "Read the words to search from stdin instead of the built-in dictionary, keeping \
+                their order; the pattern then has to be an argument",
+        conflicts_with_all = ["dict", "interactive"]
+    )]
+    stdin: bool,
+
+    #[arg(help = "Pattern to match against; read from stdin if not given and stdin is piped")]
+    pattern: Option<String>,
+
+    #[arg(
+        long = "pattern",
+        help = "Another pattern for the matches to be combined with by --combine; repeat for \
+                more, or give only these in place of the pattern argument",
+        value_name = "REGEX",
+        conflicts_with_all = [
+            "anagram",
+            "buildable",
+            "pattern_dots",
+            "rhyme",
+            "prefix",
+            "fuzzy",
+            "interactive",
+        ]
+    )]
+    patterns: Vec<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Combine::And,
+        help = "Whether words have to match every pattern given with --pattern or just one",
+        requires = "patterns"
+    )]
+    combine: Combine,
+
+    #[arg(
+        short,
+        long,
+        help = "Find anagrams of these letters instead; '?' stands for any one letter",
+        value_name = "LETTERS",
+        conflicts_with = "pattern"
+    )]
+    anagram: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        help = "Find words that can be spelled using each of these letters at most once",
+        value_name = "LETTERS",
+        conflicts_with_all = ["pattern", "anagram"]
+    )]
+    buildable: Option<String>,
+
+    #[arg(
+        long,
+        help = "Crossword-style pattern: letters are fixed and '.', '_' or '?' is any one letter, e.g. `..t.r..`",
+        value_name = "DOTS",
+        conflicts_with_all = ["pattern", "anagram", "buildable"]
+    )]
+    pattern_dots: Option<String>,
+
+    #[arg(
+        long,
+        help = "Find words ending in the same letters as this one (by spelling, not sound)",
+        value_name = "WORD",
+        conflicts_with_all = ["pattern", "anagram", "buildable", "pattern_dots"]
+    )]
+    rhyme: Option<String>,
+
+    #[arg(
+        long,
+        help = "List every word sharing at least this many final letters, not just the closest",
+        value_name = "N",
+        requires = "rhyme"
+    )]
+    rhyme_len: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Find words starting with these letters, faster than the same regex",
+        value_name = "STR",
+        conflicts_with_all = ["pattern", "anagram", "buildable", "pattern_dots", "rhyme"]
+    )]
+    prefix: Option<String>,
+
+    #[arg(
+        long,
+        help = "Find words within --distance letter insertions, deletions or substitutions of \
+                this one, closest first",
+        value_name = "WORD",
+        conflicts_with_all = ["pattern", "anagram", "buildable", "pattern_dots", "rhyme", "prefix"]
+    )]
+    fuzzy: Option<String>,
+
+    #[arg(
+        long,
+        help = "How far off a --fuzzy match can be [default: 1]",
+        value_name = "N",
+        requires = "fuzzy"
+    )]
+    distance: Option<usize>,
+
+    #[arg(
+        short,
+        long,
+        help = "Match the pattern anywhere in a word instead of against the whole word"
+    )]
+    contains: bool,
+
+    #[arg(long, help = "Match the pattern case-insensitively")]
+    ignore_case: bool,
+
+    #[arg(
+        long,
+        help = "Only show matches at least this many letters long",
+        value_name = "N",
+        conflicts_with = "stats"
+    )]
+    min_len: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Only show matches at most this many letters long",
+        value_name = "N",
+        conflicts_with = "stats"
+    )]
+    max_len: Option<usize>,
+
+    #[arg(long, help = "Show at most this many matches", value_name = "N")]
+    limit: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Print how many words match instead of the words; --limit doesn't change the count",
+        conflicts_with_all = ["interactive", "output", "random", "define", "stats"]
+    )]
+    count: bool,
+
+    #[arg(
+        long,
+        help = "Show each match's Scrabble score; `--sort score` puts the highest first",
+        conflicts_with_all = ["count", "stats"]
+    )]
+    score: bool,
+
+    #[arg(long, help = "Order to print matches in [default: dictionary order]")]
+    sort: Option<SortOrder>,
+
+    #[arg(
+        long,
+        help = "Print N of the matches picked at random, or all of them shuffled if there are \
+                fewer; with no pattern, pick from every word",
+        value_name = "N",
+        conflicts_with_all = ["interactive", "limit"]
+    )]
+    random: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Seed for --random, to pick the same words every time",
+        value_name = "SEED",
+        requires = "random"
+    )]
+    seed: Option<u64>,
+
+    #[arg(
+        long,
+        help = "JSON object or tab-separated file of word definitions, shown in the TUI",
+        value_name = "PATH"
+    )]
+    definitions: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Draw the TUI with plain ASCII (also set by a TERM of `dumb` or `vt*`)",
+        requires = "interactive"
+    )]
+    ascii_only: bool,
+
+    #[arg(
+        long,
+        help = "Color the TUI with a built-in theme instead of the config file's: `default`, \
+                `solarized`, `mono` or `high-contrast`",
+        value_name = "NAME",
+        value_parser = theme::parse_preset,
+        requires = "interactive"
+    )]
+    theme: Option<Theme>,
+
+    #[arg(
+        long,
+        help = "Print each match as `word: definition`",
+        requires = "definitions",
+        conflicts_with = "interactive"
+    )]
+    define: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Write the matches to this file instead of stdout",
+        value_name = "PATH",
+        conflicts_with = "interactive"
+    )]
+    output: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Txt,
+        help = "How to write out the matches",
+        conflicts_with = "interactive"
+    )]
+    format: OutputFormat,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ColorWhen::Auto,
+        value_name = "WHEN",
+        help = "Color the printed matches; `auto` does so on a terminal unless NO_COLOR is set",
+        conflicts_with = "interactive"
+    )]
+    color: ColorWhen,
+
+    #[arg(
+        long,
+        help = "Print the word count, a chart of word lengths and the most common first letters",
+        conflicts_with_all = [
+            "pattern",
+            "patterns",
+            "anagram",
+            "buildable",
+            "pattern_dots",
+            "rhyme",
+            "prefix",
+            "fuzzy",
+            "interactive",
+            "output",
+            "random",
+        ]
+    )]
+    stats: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum WordSubcommand {
+    #[command(name = "wordle")]
+    Wordle(WordleCommand),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// One match per line
+    Txt,
+    /// A header row and then one match per row
+    Csv,
+    /// An array of words, or of objects with definitions if `--define` is given
+    Json,
+}
+
+/// When to color matches printed as text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ColorWhen {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorWhen {
+    /// Whether to color output going to a terminal, if `terminal` is set, or elsewhere.
+    fn enabled(self, terminal: bool) -> bool {
+        match self {
+            ColorWhen::Auto => terminal && std::env::var_os("NO_COLOR").is_none(),
+            ColorWhen::Always => true,
+            ColorWhen::Never => false,
+        }
+    }
+}
+
+/// How several `--dict` word lists make one. Either way the result is sorted, with each word once
+/// however many lists or lines it's in. Words are compared exactly, so `Rust` and `rust` are
+/// different words.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum DictMode {
+    /// Words in any of the lists
+    Union,
+    /// Only words in every one of the lists
+    Intersection,
+}
+
+/// The word lists built into kit, for `--builtin` and `MatchEngine::with_builtin`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Builtin {
+    /// Every word in Webster's Unabridged Dictionary
+    #[default]
+    All,
+    /// About a thousand everyday words
+    #[value(name = "common-words")]
+    Common,
+    /// The lowercase five-letter words in `all`
+    #[value(name = "5-letter")]
+    FiveLetter,
+}
+
+impl Builtin {
+    /// The dictionary's words, one per line and sorted.
+    pub fn words(self) -> &'static str {
+        match self {
+            Builtin::All => WORDS,
+            Builtin::Common => COMMON_WORDS,
+            Builtin::FiveLetter => FIVE_LETTER_WORDS,
+        }
+    }
+
+    /// One line for each dictionary: its name, how many words it has, and what they are.
+    fn list() -> String {
+        Builtin::value_variants()
+            .iter()
+            .map(|builtin| {
+                let value = builtin.to_possible_value().unwrap();
+                format!(
+                    "{:<14}{:>7} words  {}\n",
+                    value.get_name(),
+                    builtin.words().lines().count(),
+                    value
+                        .get_help()
+                        .map(ToString::to_string)
+                        .unwrap_or_default()
+                )
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum SortOrder {
+    /// Alphabetical
+    Alpha,
+    /// Shortest first
+    Length,
+    /// Longest first
+    LengthDesc,
+    /// Highest Scrabble score first
+    Score,
+}
+
+impl SortOrder {
+    /// Sort the matches in place. Words that tie keep their relative order.
+    fn sort(&self, matches: &mut [&str]) {
+        match self {
+            SortOrder::Alpha => matches.sort(),
+            SortOrder::Length => matches.sort_by_key(|word| word.len()),
+            SortOrder::LengthDesc => matches.sort_by_key(|word| std::cmp::Reverse(word.len())),
+            SortOrder::Score => matches.sort_by_key(|word| std::cmp::Reverse(scrabble_score(word))),
+        }
+    }
+}
+
+impl WordCommand {
+    fn match_options(&self) -> MatchOptions {
+        MatchOptions {
+            contains: self.contains,
+            ignore_case: self.ignore_case,
+        }
+    }
+
+    /// The pattern argument, or if there isn't one, the first line piped in on stdin.
+    fn pattern(&self) -> Result<String, KitError> {
+        let pattern = if self.stdin || stdin().is_terminal() {
+            self.pattern_from(None)
+        } else {
+            self.pattern_from(Some(&mut stdin().lock()))
+        };
+        pattern.map_err(KitError::InvalidArgument)
+    }
+
+    /// The pattern argument, or the first line of `piped`. The argument can't be required at the
+    /// clap layer, since then a pattern couldn't be piped in instead.
+    fn pattern_from(&self, piped: Option<&mut dyn BufRead>) -> Result<String, String> {
+        match (&self.pattern, piped) {
+            (Some(pattern), _) => Ok(pattern.clone()),
+            (None, _) if !self.patterns.is_empty() => Ok(self.patterns[0].clone()),
+            (None, Some(piped)) => read_pattern(piped),
+            (None, None) if self.stdin => {
+                Err("a pattern is required with --stdin, which reads the words instead".to_string())
+            }
+            (None, None) => Err(
+                "a pattern is required unless --interactive is set; pass one or pipe one in"
+                    .to_string(),
+            ),
+        }
+    }
+
+    /// The `--pattern`s to combine with the one from `pattern`, which is the first of them if
+    /// there's no pattern argument.
+    fn extra_patterns(&self) -> &[String] {
+        match self.pattern {
+            Some(_) => &self.patterns,
+            None => self.patterns.get(1..).unwrap_or_default(),
+        }
+    }
+
+    fn lengths(&self) -> Result<LengthBounds, String> {
+        match (self.min_len, self.max_len) {
+            (Some(min), Some(max)) if min > max => Err(format!(
+                "--min-len {} is more than --max-len {}, so nothing could match",
+                min, max
+            )),
+            (min, max) => Ok(LengthBounds { min, max }),
+        }
+    }
+
+    fn words(&self) -> Result<Cow<'static, str>, KitError> {
+        if self.stdin {
+            let words = read_words(&mut stdin().lock()).map_err(|e| {
+                KitError::Io(io::Error::new(
+                    e.kind(),
+                    format!("cannot read the word list from stdin: {}", e),
+                ))
+            })?;
+            return Ok(Cow::Owned(words));
+        }
+        match self.dict.as_slice() {
+            [] => Ok(Cow::Borrowed(self.builtin.words())),
+            [path] => Ok(Cow::Owned(load_words(path)?)),
+            paths => {
+                let lists = paths
+                    .iter()
+                    .map(|path| load_words(path))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Cow::Owned(merge_word_lists(&lists, self.dict_mode)))
+            }
+        }
+    }
+
+    pub async fn run(&self) -> Result<(), KitError> {
+        if let Some(WordSubcommand::Wordle(wordle)) = &self.subcommand {
+            return wordle.run();
+        }
+        if self.list_dicts {
+            print!("{}", Builtin::list());
+            return Ok(());
+        }
+        let words = self.words()?;
+        if self.stats {
+            // Bars only make sense on a terminal; piped, the counts are easier to work with alone.
+            let width = stdout()
+                .is_terminal()
+                .then(|| terminal::size().map_or(80, |(columns, _)| columns as usize));
+            print!("{}", stats::compute(&words).report(width));
+            return Ok(());
+        }
+        let lengths = self.lengths().map_err(KitError::InvalidArgument)?;
+        let definitions = self
+            .definitions
+            .as_deref()
+            .map(load_definitions)
+            .transpose()?;
+        if self.interactive {
+            if !with_tui::is_terminal() {
+                return Err(KitError::Terminal(
+                    "--interactive needs a terminal; pass a pattern to print the matches instead"
+                        .to_string(),
+                ));
+            }
+            let theme = Config::load()?
+                .theme
+                .overridden_by(self.theme)
+                .with_ascii_only(self.ascii_only);
+            let mut app = WordRegex::new(
+                words,
+                self.match_options(),
+                lengths,
+                self.limit,
+                self.score,
+                theme,
+                definitions,
+            );
+            let history_path = history::default_path();
+            app.history = History::new(
+                history_path
+                    .as_deref()
+                    .map(history::load)
+                    .unwrap_or_default(),
+            );
+            run_app(&mut app).await?;
+            // The pattern on screen at the end counts as entered, since it is often the one
+            // that was being looked for.
+            app.history.push(&app.input.text);
+            if let Some(path) = history_path {
+                if let Err(e) = history::save(&path, app.history.entries()) {
+                    log::warn!(
+                        "cannot save the pattern history to {}: {}",
+                        path.display(),
+                        e
+                    );
+                }
+            }
+        } else {
+            let modes = (
+                &self.anagram,
+                &self.buildable,
+                &self.pattern_dots,
+                &self.rhyme,
+                &self.prefix,
+                &self.fuzzy,
+            );
+            let match_engine = match modes {
+                (Some(letters), _, _, _, _, _) => {
+                    MatchEngine::with_mode(letters.clone(), MatchMode::Anagram)
+                }
+                (_, Some(letters), _, _, _, _) => {
+                    MatchEngine::with_mode(letters.clone(), MatchMode::Buildable)
+                }
+                (_, _, Some(dots), _, _, _) => {
+                    MatchEngine::new(dots_to_regex(dots), self.match_options())
+                }
+                (_, _, _, Some(word), _, _) => {
+                    MatchEngine::with_mode(word.clone(), MatchMode::Rhyme(self.rhyme_len))
+                }
+                (_, _, _, _, Some(prefix), _) => {
+                    MatchEngine::with_mode(prefix.clone(), MatchMode::Prefix)
+                }
+                (_, _, _, _, _, Some(word)) => MatchEngine::with_mode(
+                    word.clone(),
+                    MatchMode::Fuzzy(self.distance.unwrap_or(1)),
+                ),
+                // Every word matches `.+`, for picking at random from all of them.
+                _ if self.random.is_some() => MatchEngine::new(
+                    self.pattern
+                        .clone()
+                        .or_else(|| self.patterns.first().cloned())
+                        .unwrap_or_else(|| ".+".to_string()),
+                    self.match_options(),
+                ),
+                _ => MatchEngine::new(self.pattern()?, self.match_options()),
+            }
+            .with_words(words)
+            .with_patterns(self.extra_patterns().to_vec(), self.combine);
+            let mut matches = match_engine.matches()?;
+            lengths.retain(&mut matches);
+            if self.count {
+                println!("{}", matches.len());
+                return Ok(());
+            }
+            if let Some(n) = self.random {
+                matches = pick_random(&matches, n, self.seed);
+            }
+            if let Some(order) = self.sort {
+                order.sort(&mut matches);
+            }
+            let n_total = matches.len();
+            matches.truncate(self.limit.unwrap_or(n_total));
+            let definitions = definitions.filter(|_| self.define);
+            match &self.output {
+                Some(path) => save_matches(
+                    path,
+                    &matches,
+                    definitions.as_ref(),
+                    self.score,
+                    self.fuzzy.as_deref(),
+                    self.format,
+                )?,
+                None => {
+                    let coloring = (self.format == OutputFormat::Txt
+                        && self.color.enabled(stdout().is_terminal()))
+                    .then(|| Coloring {
+                        highlight: match_engine.highlight_regex(),
+                    });
+                    write_matches(
+                        &mut stdout().lock(),
+                        &matches,
+                        definitions.as_ref(),
+                        self.score,
+                        self.fuzzy.as_deref(),
+                        self.format,
+                        coloring.as_ref(),
+                    )?;
+                    if self.format == OutputFormat::Txt && matches.len() < n_total {
+                        println!("... ({} more)", n_total - matches.len());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How long a search can run before the TUI says it's too slow
+const SEARCH_TIMEOUT: Duration = Duration::from_millis(500);
+
+struct WordRegex {
+    /// Has the same words and pattern as the searcher's engine, for resolving and highlighting
+    /// the matches it finds
+    match_engine: MatchEngine,
+    searcher: Searcher,
+    /// What has been typed, which is copied to the match engine's pattern after every edit
+    input: Input,
+    /// Patterns entered with `<Enter>` in this run and earlier ones, for `^P` and `^N` to recall
+    history: History,
+    /// Index of the selected match. The page shown is whichever one it falls on.
+    selected: usize,
+    /// Rows per column and matches per page as of the last draw, which depend on the terminal size
+    n_rows: usize,
+    page_capacity: usize,
+    lengths: LengthBounds,
+    limit: Option<usize>,
+    /// Whether to show each match's Scrabble score beside it
+    scores: bool,
+    show_help: bool,
+    theme: Theme,
+    /// Shown in a side panel for the selected match, if given
+    definitions: Option<Definitions>,
+    /// The outcome of the last save, shown until the next key press
+    status: Option<String>,
+    /// The outcome of the last search to finish, which stays on screen while the next one runs
+    searched: Searched,
+    /// The pattern being searched for and when the search started, until its results come in
+    pending: Option<(String, Instant)>,
+    /// Whether a search has finished since the last draw
+    dirty: bool,
+    /// Whether the last draw said the search was too slow
+    drawn_too_slow: bool,
+}
+
+/// Runs the TUI's searches on a thread of its own, so that a slow pattern can't freeze it.
+struct Searcher {
+    patterns: Sender<String>,
+    results: Receiver<Searched>,
+}
+
+/// Where the matches for `pattern` are in the word list, or why it didn't parse.
+struct Searched {
+    pattern: String,
+    result: Result<Vec<Range<usize>>, regex::Error>,
+    elapsed: Duration,
+}
+
+impl Searcher {
+    fn spawn(mut engine: MatchEngine) -> Self {
+        let (patterns, rx_patterns) = mpsc::channel::<String>();
+        let (tx_results, results) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(mut pattern) = rx_patterns.recv() {
+                // Anything typed while the last search ran is out of date already.
+                while let Ok(newer) = rx_patterns.try_recv() {
+                    pattern = newer;
+                }
+                engine.pattern = pattern;
+                let started = Instant::now();
+                let result = engine
+                    .matches()
+                    .map(|words| words.iter().map(|word| engine.range_of(word)).collect());
+                let searched = Searched {
+                    pattern: engine.pattern.clone(),
+                    result,
+                    elapsed: started.elapsed(),
+                };
+                if tx_results.send(searched).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { patterns, results }
+    }
+}
+
+impl WithTui for WordRegex {}
+
+impl WordRegex {
+    fn new(
+        words: Cow<'static, str>,
+        match_options: MatchOptions,
+        lengths: LengthBounds,
+        limit: Option<usize>,
+        scores: bool,
+        theme: Theme,
+        definitions: Option<Definitions>,
+    ) -> Self {
+        let searcher = Searcher::spawn(
+            MatchEngine::new("".to_string(), match_options).with_words(words.clone()),
+        );
+        let mut app = Self {
+            match_engine: MatchEngine::new("".to_string(), match_options).with_words(words),
+            searcher,
+            input: Input::default(),
+            history: History::default(),
+            selected: 0,
+            n_rows: 0,
+            page_capacity: 0,
+            lengths,
+            limit,
+            scores,
+            show_help: false,
+            theme,
+            definitions,
+            status: None,
+            searched: Searched {
+                pattern: "".to_string(),
+                result: Ok(vec![]),
+                elapsed: Duration::ZERO,
+            },
+            pending: None,
+            dirty: true,
+            drawn_too_slow: false,
+        };
+        app.edited();
+        app
+    }
+
+    /// Search for the input as edited, starting over from the first match.
+    fn edited(&mut self) {
+        self.match_engine.pattern = self.input.text.clone();
+        self.selected = 0;
+        // The searcher only stops once this end of the channel is dropped.
+        let _ = self.searcher.patterns.send(self.input.text.clone());
+        self.pending = Some((self.input.text.clone(), Instant::now()));
+    }
+
+    /// Put a pattern from the history in the input, with the cursor at its end.
+    fn recall(&mut self, pattern: Option<String>) {
+        if let Some(pattern) = pattern {
+            self.input.cursor = pattern.chars().count();
+            self.input.text = pattern;
+            self.edited();
+        }
+    }
+
+    /// Take in any searches that have finished since the last call.
+    fn receive(&mut self) {
+        while let Ok(searched) = self.searcher.results.try_recv() {
+            if self
+                .pending
+                .as_ref()
+                .is_some_and(|(pattern, _)| *pattern == searched.pattern)
+            {
+                self.pending = None;
+            }
+            self.searched = searched;
+            self.dirty = true;
+        }
+    }
+
+    fn too_slow(&self) -> bool {
+        self.pending
+            .as_ref()
+            .is_some_and(|(_, started)| started.elapsed() >= SEARCH_TIMEOUT)
+    }
+
+    /// Write the matches shown to a timestamped file in the current directory, returning a
+    /// message saying where they went or what went wrong.
+    fn save(&self) -> String {
+        let mut matches = match &self.searched.result {
+            Ok(ranges) => self.match_engine.resolve(ranges),
+            Err(_) => return "Nothing to save: invalid regex".to_string(),
+        };
+        self.lengths.retain(&mut matches);
+        matches.truncate(self.limit.unwrap_or(matches.len()));
+        let path = PathBuf::from(format!(
+            "kit-words-{}.txt",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        match save_matches(
+            &path,
+            &matches,
+            self.definitions.as_ref(),
+            self.scores,
+            None,
+            OutputFormat::Txt,
+        ) {
+            Ok(()) => format!("Saved {} matches to {}", matches.len(), path.display()),
+            Err(e) => e.to_string(),
+        }
+    }
+}
+
+impl App for WordRegex {
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>) -> Result<(), KitError> {
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(2)
+            .constraints(
+                [
+                    layout::Constraint::Length(3),
+                    layout::Constraint::Min(0),
+                    layout::Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(f.size());
+        let (matches_area, definition_area) = match self.definitions {
+            Some(_) => {
+                let columns = layout::Layout::default()
+                    .direction(layout::Direction::Horizontal)
+                    .constraints([layout::Constraint::Min(0), layout::Constraint::Length(32)])
+                    .split(chunks[1]);
+                (columns[0], Some(columns[1]))
+            }
+            None => (chunks[1], None),
+        };
+        let input_block = match &self.status {
+            Some(status) => self.theme.block().title(status.as_str()),
+            None => self.theme.block(),
+        };
+        let prompt = "> ";
+        let input_widget = widgets::Paragraph::new(format!("{}{}", prompt, self.input.text))
+            .block(input_block)
+            .wrap(widgets::Wrap { trim: true });
+        // Inside the border, and clamped to it in case the input is longer than the box.
+        let cursor_x = chunks[0].x + 1 + (prompt.len() + self.input.cursor) as u16;
+        f.set_cursor(
+            cursor_x.min(chunks[0].right().saturating_sub(2)),
+            chunks[0].y + 1,
+        );
+        self.dirty = false;
+        self.drawn_too_slow = self.too_slow();
+        let (mut matches, error) = match &self.searched.result {
+            Ok(ranges) => (self.match_engine.resolve(ranges), None),
+            Err(e) => (vec![], Some(e)),
+        };
+        self.lengths.retain(&mut matches);
+        let n_total = matches.len();
+        matches.truncate(self.limit.unwrap_or(n_total));
+        let column_spacing = 2;
+        let len_longest_match = matches.iter().map(|s| s.chars().count()).max().unwrap_or(0);
+        // Scores go in a right-aligned column of their own after the words.
+        let score_width = if self.scores {
+            let highest = matches.iter().map(|s| scrabble_score(s)).max().unwrap_or(0);
+            highest.to_string().len()
+        } else {
+            0
+        };
+        // leave out the table borders
+        let inner_width = matches_area.width.saturating_sub(2) as usize;
+        let n_rows = matches_area.height.saturating_sub(2) as usize;
+        if n_rows == 0 || inner_width == 0 {
+            self.n_rows = 0;
+            self.page_capacity = 0;
+            f.render_widget(
+                widgets::Paragraph::new("Terminal too small").wrap(widgets::Wrap { trim: true }),
+                f.size(),
+            );
+            return Ok(());
+        }
+        // Words too long for even one column get cut short to fit, scores and all.
+        let cell_width =
+            (len_longest_match + score_width + (score_width > 0) as usize).min(inner_width);
+        let word_width = cell_width
+            .saturating_sub(score_width + (score_width > 0) as usize)
+            .max(1);
+        let n_columns = ((inner_width + column_spacing) / (cell_width + column_spacing)).max(1);
+        let column_widths = vec![layout::Constraint::Length(cell_width as u16); n_columns];
+        let n_words_visible = n_rows * n_columns;
+        self.n_rows = n_rows;
+        self.page_capacity = n_words_visible;
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+        let total_pages = n_pages(matches.len(), n_words_visible);
+        let current_page = self
+            .selected
+            .checked_div(n_words_visible)
+            .unwrap_or(0)
+            .min(total_pages - 1);
+        let start_at = current_page * n_words_visible;
+        let highlight_regex = self.match_engine.highlight_regex();
+        let table_entries: Vec<widgets::Row> = transpose(
+            matches
+                .iter()
+                .enumerate()
+                .skip(start_at)
+                .take(n_words_visible)
+                .chunks(n_rows.max(1))
+                .into_iter()
+                .map(|chunk| chunk.collect())
+                .collect(),
+        )
+        .iter()
+        .map(|row| {
+            widgets::Row::new(row.iter().map(|&(i, s)| {
+                let mut spans = ellipsize(
+                    highlight_match(
+                        s,
+                        highlight_regex.as_ref(),
+                        self.theme.emphasized(self.theme.highlight),
+                    ),
+                    word_width,
+                );
+                if self.scores {
+                    spans.0.push(Span::styled(
+                        format!(
+                            "{:>width$}",
+                            scrabble_score(s),
+                            width = cell_width.saturating_sub(spans_len(&spans))
+                        ),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ));
+                }
+                let cell = widgets::Cell::from(spans);
+                if i == self.selected {
+                    cell.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    cell
+                }
+            }))
+        })
+        .collect();
+        let matches_table = widgets::Table::new(table_entries)
+            .widths(column_widths.as_slice())
+            .column_spacing(column_spacing as u16)
+            .block(self.theme.block().title(if error.is_some() {
+                "Matches (invalid regex)".to_string()
+            } else if matches.len() < n_total {
+                format!("Matches ({} total, showing {})", n_total, matches.len())
+            } else {
+                format!("Matches ({} total)", n_total)
+            }));
+        let status_line = match &error {
+            _ if self.drawn_too_slow => Span::styled(
+                "Pattern too slow, still searching; showing the last matches",
+                Style::default().fg(Color::Yellow),
+            ),
+            Some(e) => Span::styled(regex_error_summary(e), Style::default().fg(Color::Red)),
+            None => Span::raw(format!(
+                "{} in {:.2}ms, page {}/{}",
+                count_matches(n_total),
+                self.searched.elapsed.as_secs_f64() * 1000.0,
+                current_page + 1,
+                total_pages
+            )),
+        };
+        f.render_widget(input_widget, chunks[0]);
+        f.render_widget(matches_table, matches_area);
+        f.render_widget(widgets::Paragraph::new(status_line), chunks[2]);
+        if let (Some(area), Some(definitions)) = (definition_area, &self.definitions) {
+            let text = match matches.get(self.selected) {
+                Some(word) => vec![
+                    Spans::from(Span::styled(
+                        *word,
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )),
+                    Spans::from(definition(definitions, word)),
+                ],
+                None => vec![],
+            };
+            let definition_widget = widgets::Paragraph::new(text)
+                .block(self.theme.block().title("Definition"))
+                .wrap(widgets::Wrap { trim: true });
+            f.render_widget(definition_widget, area);
+        }
+        if self.show_help {
+            let help_area = centered_rect(44, 15, chunks[1]);
+            f.render_widget(widgets::Clear, help_area);
+            f.render_widget(help_table(&self.theme), help_area);
+        }
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<ControlFlow<()>, KitError> {
+        if let Event::Key(_) = event {
+            self.status = None;
+        }
+        match event {
+            Event::Key(key) => match key {
+                KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
+                } => return Ok(ControlFlow::Break(())),
+                KeyEvent {
+                    code: KeyCode::Char('s'),
+                    modifiers: KeyModifiers::CONTROL,
+                } => self.status = Some(self.save()),
+                KeyEvent {
+                    code: KeyCode::Char('u'),
+                    modifiers: KeyModifiers::CONTROL,
+                } => self.selected = self.selected.saturating_sub(self.page_capacity),
+                KeyEvent {
+                    code: KeyCode::Char('d'),
+                    modifiers: KeyModifiers::CONTROL,
+                } => self.selected += self.page_capacity,
+                // Up and down are taken by the matches, so the history goes by the keys that
+                // step through it in a shell when the arrows are busy elsewhere.
+                KeyEvent {
+                    code: KeyCode::Char('p'),
+                    modifiers: KeyModifiers::CONTROL,
+                } => {
+                    let pattern = self.history.previous(&self.input.text).map(str::to_string);
+                    self.recall(pattern);
+                }
+                KeyEvent {
+                    code: KeyCode::Char('n'),
+                    modifiers: KeyModifiers::CONTROL,
+                } => {
+                    let pattern = self.history.next().map(str::to_string);
+                    self.recall(pattern);
+                }
+                KeyEvent {
+                    code: KeyCode::Enter,
+                    ..
+                } => self.history.push(&self.input.text),
+                // Matches run down each column, so up and down step by one and tab by a column.
+                // The next draw clamps the selection to the last match.
+                KeyEvent {
+                    code: KeyCode::Up, ..
+                } => self.selected = self.selected.saturating_sub(1),
+                KeyEvent {
+                    code: KeyCode::Down,
+                    ..
+                } => self.selected += 1,
+                KeyEvent {
+                    code: KeyCode::BackTab,
+                    ..
+                } => self.selected = self.selected.saturating_sub(self.n_rows),
+                KeyEvent {
+                    code: KeyCode::Tab, ..
+                } => self.selected += self.n_rows,
+                KeyEvent {
+                    code: KeyCode::Left,
+                    ..
+                } => self.input.cursor = self.input.cursor.saturating_sub(1),
+                KeyEvent {
+                    code: KeyCode::Right,
+                    ..
+                } => self.input.cursor = (self.input.cursor + 1).min(self.input.len()),
+                KeyEvent {
+                    code: KeyCode::Home,
+                    ..
+                } => self.input.cursor = 0,
+                KeyEvent {
+                    code: KeyCode::End, ..
+                } => self.input.cursor = self.input.len(),
+                // A regex can't start with '?', so it is free to toggle help when the input
+                // is empty. Anywhere else it is typed as usual.
+                KeyEvent {
+                    code: KeyCode::Char('?'),
+                    ..
+                } if self.match_engine.pattern.is_empty() => self.show_help = !self.show_help,
+                KeyEvent {
+                    code: KeyCode::F(1),
+                    ..
+                } => self.show_help = !self.show_help,
+                KeyEvent {
+                    code: KeyCode::Char(c),
+                    ..
+                } => {
+                    self.input.insert(c);
+                    self.history.stop();
+                    self.edited();
+                }
+                KeyEvent {
+                    code: KeyCode::Backspace,
+                    ..
+                } => {
+                    self.input.backspace();
+                    self.history.stop();
+                    self.edited();
+                }
+                KeyEvent {
+                    code: KeyCode::Delete,
+                    ..
+                } => {
+                    self.input.delete();
+                    self.history.stop();
+                    self.edited();
+                }
+                KeyEvent {
+                    code: KeyCode::Esc, ..
+                } => return Ok(ControlFlow::Break(())),
+                _ => {}
+            },
+            // The next draw picks up the new size, recomputing the columns and clearing the
+            // screen, so there is nothing more to do here.
+            Event::Resize(_, _) => {}
+            Event::Mouse(_) => {}
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    fn tick(&mut self) -> Result<ControlFlow<()>, KitError> {
+        self.receive();
+        Ok(ControlFlow::Continue(()))
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.dirty || self.too_slow() != self.drawn_too_slow
+    }
+
+    /// Often enough that results seem to come in as soon as they're ready.
+    fn tick_rate(&self) -> Option<Duration> {
+        Some(Duration::from_millis(20))
+    }
+
+    fn ascii_only(&self) -> bool {
+        self.theme.ascii_only
+    }
+
+    fn title(&self) -> Option<String> {
+        Some(format!(
+            "{} - {}",
+            std::env::args().join(" "),
+            self.match_engine.pattern,
+        ))
+    }
+}
+
+/// A line of text being edited at a cursor. The cursor counts chars rather than bytes, so that
+/// editing can't land in the middle of one.
+#[derive(Debug, Default)]
+struct Input {
+    text: String,
+    /// How many chars come before the cursor
+    cursor: usize,
+}
+
+impl Input {
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn byte_index(&self) -> usize {
+        self.text
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    fn insert(&mut self, c: char) {
+        self.text.insert(self.byte_index(), c);
+        self.cursor += 1;
+    }
+
+    /// Delete the char before the cursor, if any.
+    fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.text.remove(self.byte_index());
+        }
+    }
+
+    /// Delete the char under the cursor, if any.
+    fn delete(&mut self) {
+        if self.cursor < self.len() {
+            self.text.remove(self.byte_index());
+        }
+    }
+}
+
+/// `--min-len` and `--max-len`, checked against the words a `MatchEngine` finds. Lengths count
+/// chars, so an accented letter is one letter.
+#[derive(Clone, Copy, Debug, Default)]
+struct LengthBounds {
+    min: Option<usize>,
+    max: Option<usize>,
+}
+
+impl LengthBounds {
+    fn retain(&self, words: &mut Vec<&str>) {
+        if self.min.is_none() && self.max.is_none() {
+            return;
+        }
+        words.retain(|word| {
+            let len = word.chars().count();
+            self.min.is_none_or(|min| len >= min) && self.max.is_none_or(|max| len <= max)
+        });
+    }
+}
+
+/// How `MatchEngine` interprets its pattern.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// A regex that has to match a whole word.
+    #[default]
+    Regex,
+    /// Letters that a word has to be a rearrangement of; `?` stands for any one letter.
+    Anagram,
+    /// Letters that a word has to be spelled from, using each one at most once.
+    Buildable,
+    /// A word that others have to end the same way as, ignoring case. This goes by spelling, so
+    /// `though` and `rough` count and `blue` and `through` don't. With `Some(n)` any word sharing
+    /// at least the last `n` letters matches, and otherwise only those sharing the longest ending
+    /// that any word does.
+    Rhyme(Option<usize>),
+    /// Letters that a word has to start with, case and all. The words are found by binary search
+    /// over the word list in sorted order, and come out in that order.
+    Prefix,
+    /// A word that others have to be within this many single-letter insertions, deletions or
+    /// substitutions of, ignoring case. The word itself is as close as can be, at 0.
+    Fuzzy(usize),
+}
+
+/// Tweaks to how a `MatchMode::Regex` pattern is applied.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MatchOptions {
+    /// Match anywhere within a word rather than against the whole word.
+    pub contains: bool,
+    pub ignore_case: bool,
+}
+
+/// How `MatchEngine::with_patterns` combines the matches of several regexes. Either way they
+/// stay in word list order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Combine {
+    /// Words matching every pattern
+    #[default]
+    And,
+    /// Words matching any of the patterns
+    Or,
+}
+
+/// Finds the words in a word list that match a pattern, for `kit word` and for use as a library:
+///
+/// ```
+/// use kit::{MatchEngine, MatchMode};
+///
+/// let engine = MatchEngine::with_mode("tca".to_string(), MatchMode::Anagram)
+///     .with_words("act\ncat\ndog\ntaco\n");
+/// assert_eq!(engine.matching_words().unwrap(), ["act", "cat"]);
+/// ```
+#[derive(Debug)]
+pub struct MatchEngine {
+    /// Newline-delimited words to search, the built-in dictionary unless replaced by `with_words`
+    words: Cow<'static, str>,
+    pattern: String,
+    /// More `MatchMode::Regex` patterns, whose matches are combined with the pattern's
+    patterns: Vec<String>,
+    combine: Combine,
+    mode: MatchMode,
+    options: MatchOptions,
+    cache: RefCell<SearchCache>,
+}
+
+/// What the interactive TUI would otherwise redo on every keystroke and redraw.
+#[derive(Debug, Default)]
+struct SearchCache {
+    regexes: HashMap<String, Regex>,
+    /// The last pattern that searched successfully and where its matches are in the word list
+    last: Option<(String, Vec<Range<usize>>)>,
+    /// Where each word is in the word list, in sorted order, for `MatchMode::Prefix`
+    sorted: Option<Vec<Range<usize>>>,
+}
+
+impl MatchEngine {
+    /// Match `pattern` as a regex against the built-in dictionary.
+    pub fn new(pattern: String, options: MatchOptions) -> Self {
+        Self {
+            words: Cow::Borrowed(WORDS),
+            pattern,
+            patterns: vec![],
+            combine: Combine::And,
+            mode: MatchMode::Regex,
+            options,
+            cache: RefCell::default(),
+        }
+    }
+
+    /// Interpret `pattern` according to `mode`, searching the built-in dictionary.
+    pub fn with_mode(pattern: String, mode: MatchMode) -> Self {
+        Self {
+            words: Cow::Borrowed(WORDS),
+            pattern,
+            patterns: vec![],
+            combine: Combine::And,
+            mode,
+            options: MatchOptions::default(),
+            cache: RefCell::default(),
+        }
+    }
+
+    /// Search one of the other built-in dictionaries instead of `Builtin::All`.
+    pub fn with_builtin(self, builtin: Builtin) -> Self {
+        self.with_words(builtin.words())
+    }
+
+    /// Search `words`, one per line, instead of the built-in dictionary.
+    pub fn with_words(mut self, words: impl Into<Cow<'static, str>>) -> Self {
+        self.words = words.into();
+        self.cache = RefCell::default();
+        self
+    }
+
+    /// Match `patterns` as well as the pattern, keeping the words that match all of them or any
+    /// of them according to `combine`. Only `MatchMode::Regex` looks at them.
+    pub fn with_patterns(mut self, patterns: Vec<String>, combine: Combine) -> Self {
+        self.patterns = patterns;
+        self.combine = combine;
+        self.cache = RefCell::default();
+        self
+    }
+
+    /// The matches for the current pattern. The same pattern as last time gets the same matches
+    /// back without a search, and in `contains` mode a pattern that only adds letters to the end
+    /// of the last one just searches the last one's matches.
+    pub fn matches(&self) -> Result<Vec<&str>, regex::Error> {
+        let last = self.cache.borrow_mut().last.take();
+        let result = match &last {
+            Some((pattern, ranges)) if *pattern == self.pattern => Ok(self.resolve(ranges)),
+            Some((pattern, ranges))
+                if self.mode == MatchMode::Regex
+                    && self.options.contains
+                    && self.patterns.is_empty()
+                    && narrows(pattern, &self.pattern) =>
+            {
+                log::trace!("narrowing {} matches of {:?}", ranges.len(), pattern);
+                let regex = self.contains_regex()?;
+                let mut words = self.resolve(ranges);
+                words.retain(|word| regex.is_match(word));
+                Ok(words)
+            }
+            _ => match self.mode {
+                MatchMode::Regex => self.regex_matches(),
+                MatchMode::Anagram => Ok(self.anagram_matches()),
+                MatchMode::Buildable => Ok(self.buildable_matches()),
+                MatchMode::Rhyme(min_len) => Ok(self.rhyme_matches(min_len)),
+                MatchMode::Prefix => Ok(self.prefix_matches()),
+                MatchMode::Fuzzy(max) => Ok(self.fuzzy_matches(max)),
+            },
+        };
+        // An invalid pattern keeps the last good search around to narrow from later.
+        self.cache.borrow_mut().last = match &result {
+            Ok(words) => Some((
+                self.pattern.clone(),
+                words.iter().map(|word| self.range_of(word)).collect(),
+            )),
+            Err(_) => last,
+        };
+        result
+    }
+
+    /// Like `matches`, but with copies of the words that outlive the engine.
+    pub fn matching_words(&self) -> Result<Vec<String>, regex::Error> {
+        Ok(self.matches()?.into_iter().map(str::to_string).collect())
+    }
+
+    fn resolve(&self, ranges: &[Range<usize>]) -> Vec<&str> {
+        ranges
+            .iter()
+            .map(|range| &self.words[range.clone()])
+            .collect()
+    }
+
+    /// Where `word`, which has to be a slice of the word list, sits within it.
+    fn range_of(&self, word: &str) -> Range<usize> {
+        let start = word.as_ptr() as usize - self.words.as_ptr() as usize;
+        start..start + word.len()
+    }
+
+    fn compile(&self, pattern: &str) -> Result<Regex, regex::Error> {
+        if let Some(regex) = self.cache.borrow().regexes.get(pattern) {
+            return Ok(regex.clone());
+        }
+        let regex = compile(pattern)?;
+        self.cache
+            .borrow_mut()
+            .regexes
+            .insert(pattern.to_string(), regex.clone());
+        Ok(regex)
+    }
+
+    fn flags(&self) -> &'static str {
+        if self.options.ignore_case {
+            "(?i)"
+        } else {
+            ""
+        }
+    }
+
+    /// The regex for `contains` mode, which matches anywhere within a single word.
+    fn contains_regex(&self) -> Result<Regex, regex::Error> {
+        self.compile(&format!("{}{}", self.flags(), self.pattern))
+    }
+
+    /// The regex to highlight within each matching word, if any part of a word is worth
+    /// highlighting. Only `contains` mode has one; in every other mode the whole word matches.
+    /// With more than one pattern, whatever any of them matches is highlighted.
+    fn highlight_regex(&self) -> Option<Regex> {
+        match self.mode {
+            MatchMode::Regex if self.options.contains && self.patterns.is_empty() => {
+                self.contains_regex().ok()
+            }
+            MatchMode::Regex if self.options.contains => {
+                let alternatives = std::iter::once(&self.pattern)
+                    .chain(&self.patterns)
+                    .map(|pattern| format!("(?:{})", pattern))
+                    .join("|");
+                self.compile(&format!("{}{}", self.flags(), alternatives))
+                    .ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// The words matching the pattern, combined with those matching any other patterns.
+    fn regex_matches(&self) -> Result<Vec<&str>, regex::Error> {
+        let matches = self.pattern_matches(&self.pattern)?;
+        if self.patterns.is_empty() {
+            return Ok(matches);
+        }
+        let others = self
+            .patterns
+            .iter()
+            .map(|pattern| self.pattern_matches(pattern))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Words are told apart by where they are in the word list, which also puts a union back
+        // in its order.
+        Ok(match self.combine {
+            Combine::And => {
+                let others: Vec<HashSet<usize>> = others
+                    .iter()
+                    .map(|words| words.iter().map(|word| self.range_of(word).start).collect())
+                    .collect();
+                matches
+                    .into_iter()
+                    .filter(|word| {
+                        let start = self.range_of(word).start;
+                        others.iter().all(|other| other.contains(&start))
+                    })
+                    .collect()
+            }
+            Combine::Or => std::iter::once(matches)
+                .chain(others)
+                .flatten()
+                .map(|word| (self.range_of(word).start, word))
+                .collect::<BTreeMap<_, _>>()
+                .into_values()
+                .collect(),
+        })
+    }
+
+    fn pattern_matches(&self, pattern: &str) -> Result<Vec<&str>, regex::Error> {
+        if self.options.contains {
+            // Test each word on its own so that a pattern like `t\sc` can't match across the
+            // newline between two words.
+            let regex = self.compile(&format!("{}{}", self.flags(), pattern))?;
+            return Ok(self
+                .words
+                .lines()
+                .filter(|word| regex.is_match(word))
+                .collect());
+        }
+        let result: Vec<&str> = self
+            .compile(&format!(r"(?m){}^(?:{})$", self.flags(), pattern))?
+            .find_iter(&self.words)
+            .map(|match_| match_.as_str())
+            .collect();
+        if result.len() == 1 && result[0].is_empty() {
+            Ok(vec![])
+        } else {
+            Ok(result)
+        }
+    }
+
+    fn prefix_matches(&self) -> Vec<&str> {
+        let mut cache = self.cache.borrow_mut();
+        let sorted = cache.sorted.get_or_insert_with(|| {
+            let mut ranges: Vec<Range<usize>> = self
+                .words
+                .lines()
+                .filter(|word| !word.is_empty())
+                .map(|word| self.range_of(word))
+                .collect();
+            // The built-in dictionary and merged lists are sorted already.
+            if !ranges.is_sorted_by_key(|range| &self.words[range.clone()]) {
+                log::debug!("sorting the word list for prefix searches");
+                ranges.sort_by_key(|range| &self.words[range.clone()]);
+            }
+            ranges
+        });
+        let word = |range: &Range<usize>| &self.words[range.clone()];
+        let start = sorted.partition_point(|range| word(range) < self.pattern.as_str());
+        let len = sorted[start..].partition_point(|range| word(range).starts_with(&self.pattern));
+        self.resolve(&sorted[start..start + len])
+    }
+
+    /// Anagrams come out sorted by length and then alphabetically.
+    fn anagram_matches(&self) -> Vec<&str> {
+        let mut letters: Vec<char> = self.pattern.chars().filter(|&c| c != '?').collect();
+        letters.sort_unstable();
+        let n_wildcards = self.pattern.chars().count() - letters.len();
+        let mut result: Vec<&str> = self
+            .words
+            .lines()
+            .filter(|word| is_anagram(word, &letters, n_wildcards))
+            .collect();
+        result.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+        result
+    }
+
+    /// Buildable words come out longest first, and alphabetically within each length.
+    fn buildable_matches(&self) -> Vec<&str> {
+        let bag = letter_counts(&self.pattern);
+        let mut result: Vec<&str> = self
+            .words
+            .lines()
+            .filter(|word| {
+                letter_counts(word)
+                    .iter()
+                    .all(|(c, n)| bag.get(c).is_some_and(|available| n <= available))
+            })
+            .collect();
+        result.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        result
+    }
+
+    /// Rhymes come out sharing the longest ending first, and alphabetically among those sharing
+    /// as much. The word itself is left out.
+    fn rhyme_matches(&self, min_len: Option<usize>) -> Vec<&str> {
+        let target = self.pattern.to_lowercase();
+        let mut scored: Vec<(usize, &str)> = self
+            .words
+            .lines()
+            .filter(|word| word.to_lowercase() != target)
+            .map(|word| (shared_ending(word, &target), word))
+            .filter(|&(shared, _)| shared > 0)
+            .collect();
+        let longest = scored.iter().map(|&(shared, _)| shared).max().unwrap_or(0);
+        let min_len = min_len.unwrap_or(longest).max(1);
+        scored.retain(|&(shared, _)| shared >= min_len);
+        scored.sort_by(|(shared_a, a), (shared_b, b)| shared_b.cmp(shared_a).then(a.cmp(b)));
+        scored.into_iter().map(|(_, word)| word).collect()
+    }
+
+    /// Fuzzy matches come out closest first, and alphabetically among those as close.
+    fn fuzzy_matches(&self, max: usize) -> Vec<&str> {
+        let target: Vec<char> = self.pattern.to_lowercase().chars().collect();
+        let mut scored: Vec<(usize, &str)> = self
+            .words
+            .lines()
+            .filter_map(|word| {
+                let word_chars: Vec<char> = word.to_lowercase().chars().collect();
+                bounded_edit_distance(&word_chars, &target, max).map(|distance| (distance, word))
+            })
+            .collect();
+        scored.sort();
+        scored.into_iter().map(|(_, word)| word).collect()
+    }
+}
+
+/// The Levenshtein distance between `a` and `b`, or `None` as soon as it's sure to be more than
+/// `max`. Most of a word list is that far off, so almost every word gets turned away by its
+/// length or after a few rows, without working out the whole table.
+fn bounded_edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    // Distances from a prefix of `a` to each prefix of `b`, a row at a time
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, &a_char) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b_char) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(a_char != b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+        // Every path to the end goes through this row, and distances only grow along one.
+        if row.iter().all(|&distance| distance > max) {
+            return None;
+        }
+    }
+    Some(row[b.len()]).filter(|&distance| distance <= max)
+}
+
+/// How far `word` is from `target` ignoring case, for annotating `--fuzzy` matches, which are
+/// already known to be close.
+fn edit_distance(word: &str, target: &str) -> usize {
+    let word: Vec<char> = word.to_lowercase().chars().collect();
+    let target: Vec<char> = target.to_lowercase().chars().collect();
+    bounded_edit_distance(&word, &target, usize::MAX).unwrap_or(usize::MAX)
+}
+
+/// How many letters at the end of `word` are the same as at the end of the lowercase `target`.
+fn shared_ending(word: &str, target: &str) -> usize {
+    word.chars()
+        .rev()
+        .flat_map(char::to_lowercase)
+        .zip(target.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+/// Read a pattern as the first line of `input`, without its line ending.
+fn read_pattern(input: &mut dyn BufRead) -> Result<String, String> {
+    let mut line = String::new();
+    input
+        .read_line(&mut line)
+        .map_err(|e| format!("cannot read a pattern from stdin: {}", e))?;
+    let pattern = line.trim_end_matches(['\n', '\r']);
+    if pattern.is_empty() {
+        return Err("no pattern provided, and none on stdin".to_string());
+    }
+    Ok(pattern.to_string())
+}
+
+/// Read a newline-delimited word list, accepting `\r\n` line endings as well as `\n`.
+fn load_words(path: &Path) -> Result<String, KitError> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| KitError::io_at("cannot read word list", path, e))?;
+    Ok(contents.replace("\r\n", "\n"))
+}
+
+/// Read a word list from `input` a line at a time, so that however long the stream, only the
+/// list itself is held. Blank lines are dropped and `\r\n` endings become `\n`.
+fn read_words(input: &mut dyn BufRead) -> io::Result<String> {
+    let mut words = String::new();
+    let mut line = String::new();
+    while input.read_line(&mut line)? > 0 {
+        let word = line.trim_end_matches(['\n', '\r']);
+        if !word.is_empty() {
+            words.push_str(word);
+            words.push('\n');
+        }
+        line.clear();
+    }
+    Ok(words)
+}
+
+/// Combine newline-delimited word lists into one, sorted and without duplicates.
+fn merge_word_lists(lists: &[String], mode: DictMode) -> String {
+    let sets = lists.iter().map(|list| {
+        list.lines()
+            .filter(|word| !word.is_empty())
+            .collect::<BTreeSet<_>>()
+    });
+    let merged = match mode {
+        DictMode::Union => sets.reduce(|a, b| &a | &b),
+        DictMode::Intersection => sets.reduce(|a, b| &a & &b),
+    };
+    merged
+        .unwrap_or_default()
+        .into_iter()
+        .map(|word| format!("{}\n", word))
+        .collect()
+}
+
+/// Word definitions for `--definitions`, keyed by the word exactly as it appears in the word list
+type Definitions = HashMap<String, String>;
+
+const NO_DEFINITION: &str = "(no definition)";
+
+/// Read definitions from a JSON object of words to definitions if the file name ends in `.json`,
+/// or else from lines of `word<TAB>definition`.
+fn load_definitions(path: &Path) -> Result<Definitions, KitError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| KitError::io_at("cannot read definitions", path, e))?;
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    let definitions = if is_json {
+        serde_json::from_str(&contents).map_err(|e| e.to_string())
+    } else {
+        parse_tsv_definitions(&contents)
+    };
+    definitions.map_err(|e| {
+        KitError::InvalidArgument(format!("invalid definitions {}: {}", path.display(), e))
+    })
+}
+
+/// Blank lines are skipped. If a word is defined twice the later line wins, as a repeated key
+/// would in JSON.
+fn parse_tsv_definitions(contents: &str) -> Result<Definitions, String> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| match line.split_once('\t') {
+            Some((word, definition)) => {
+                Ok((word.trim().to_string(), definition.trim().to_string()))
+            }
+            None => Err(format!("line {}: expected `word<TAB>definition`", i + 1)),
+        })
+        .collect()
+}
+
+fn definition<'a>(definitions: &'a Definitions, word: &str) -> &'a str {
+    definitions.get(word).map_or(NO_DEFINITION, String::as_str)
+}
+
+/// How text output is colored for a terminal: the matched part of each word in bold green, and
+/// scores and definitions dimmed.
+struct Coloring {
+    /// What part of a word matched, or `None` if all of it did
+    highlight: Option<Regex>,
+}
+
+impl Coloring {
+    fn word(&self, word: &str) -> String {
+        let range = match &self.highlight {
+            Some(regex) => regex
+                .find(word)
+                .filter(|found| !found.as_str().is_empty())
+                .map(|found| found.range()),
+            None => Some(0..word.len()),
+        };
+        match range {
+            Some(range) => format!(
+                "{}{}{}",
+                &word[..range.start],
+                word[range.clone()].green().bold(),
+                &word[range.end..]
+            ),
+            None => word.to_string(),
+        }
+    }
+}
+
+/// Write `matches` in `format`, including their Scrabble scores if `scores` is set, how far off
+/// they are from a `fuzzy` word if there is one, and their definitions if there are any. Only
+/// text is ever colored, and only with `coloring`.
+fn write_matches(
+    out: &mut impl Write,
+    matches: &[&str],
+    definitions: Option<&Definitions>,
+    scores: bool,
+    fuzzy: Option<&str>,
+    format: OutputFormat,
+    coloring: Option<&Coloring>,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Txt => {
+            for word in matches {
+                match coloring {
+                    Some(coloring) => write!(out, "{}", coloring.word(word))?,
+                    None => write!(out, "{}", word)?,
+                }
+                if let Some(target) = fuzzy {
+                    let distance = format!("~{}", edit_distance(word, target));
+                    match coloring {
+                        Some(_) => write!(out, " {}", distance.dim())?,
+                        None => write!(out, " {}", distance)?,
+                    }
+                }
+                if scores {
+                    let score = scrabble_score(word).to_string();
+                    match coloring {
+                        Some(_) => write!(out, " {}", score.dim())?,
+                        None => write!(out, " {}", score)?,
+                    }
+                }
+                if let Some(definitions) = definitions {
+                    let definition = definition(definitions, word);
+                    match coloring {
+                        Some(_) => write!(out, ": {}", definition.dim())?,
+                        None => write!(out, ": {}", definition)?,
+                    }
+                }
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => {
+            write!(out, "word")?;
+            if fuzzy.is_some() {
+                write!(out, ",distance")?;
+            }
+            if scores {
+                write!(out, ",score")?;
+            }
+            if definitions.is_some() {
+                write!(out, ",definition")?;
+            }
+            writeln!(out)?;
+            for word in matches {
+                write!(out, "{}", csv_field(word))?;
+                if let Some(target) = fuzzy {
+                    write!(out, ",{}", edit_distance(word, target))?;
+                }
+                if scores {
+                    write!(out, ",{}", scrabble_score(word))?;
+                }
+                if let Some(definitions) = definitions {
+                    write!(out, ",{}", csv_field(definition(definitions, word)))?;
+                }
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Json if !scores && fuzzy.is_none() && definitions.is_none() => {
+            serde_json::to_writer(&mut *out, matches)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Json => {
+            let entries: Vec<_> = matches
+                .iter()
+                .map(|word| WordEntry {
+                    word,
+                    distance: fuzzy.map(|target| edit_distance(word, target)),
+                    score: scores.then(|| scrabble_score(word)),
+                    definition: definitions.map(|definitions| definition(definitions, word)),
+                })
+                .collect();
+            serde_json::to_writer(&mut *out, &entries)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// A match in JSON output, when there's more to say about it than the word
+#[derive(Serialize)]
+struct WordEntry<'a> {
+    word: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distance: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    definition: Option<&'a str>,
+}
+
+/// Write `matches` to the file at `path`, replacing it if it exists.
+fn save_matches(
+    path: &Path,
+    matches: &[&str],
+    definitions: Option<&Definitions>,
+    scores: bool,
+    fuzzy: Option<&str>,
+    format: OutputFormat,
+) -> Result<(), KitError> {
+    let write = || {
+        let mut out = io::BufWriter::new(fs::File::create(path)?);
+        write_matches(&mut out, matches, definitions, scores, fuzzy, format, None)?;
+        out.flush()
+    };
+    write().map_err(|e| KitError::io_at("cannot write", path, e))
+}
+
+/// What `word` would score in Scrabble, before any premium squares. Letters count the same in
+/// either case, and anything other than the 26 letters counts as a blank, for nothing.
+fn scrabble_score(word: &str) -> u32 {
+    word.chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            'a' | 'e' | 'i' | 'l' | 'n' | 'o' | 'r' | 's' | 't' | 'u' => 1,
+            'd' | 'g' => 2,
+            'b' | 'c' | 'm' | 'p' => 3,
+            'f' | 'h' | 'v' | 'w' | 'y' => 4,
+            'k' => 5,
+            'j' | 'x' => 8,
+            'q' | 'z' => 10,
+            _ => 0,
+        })
+        .sum()
+}
+
+fn letter_counts(s: &str) -> HashMap<char, usize> {
+    s.chars().fold(HashMap::new(), |mut counts, c| {
+        *counts.entry(c).or_insert(0) += 1;
+        counts
+    })
+}
+
+/// Translate a `--pattern-dots` pattern into a regex: each `.`, `_` or `?` becomes `\pL`, which
+/// matches a letter but not the hyphen or apostrophe in some dictionary entries, and anything else
+/// stands for itself. `MatchEngine` anchors the regex to whole words, so the length is fixed too.
+fn dots_to_regex(dots: &str) -> String {
+    dots.chars()
+        .map(|c| match c {
+            '.' | '_' | '?' => r"\pL".to_string(),
+            c => regex::escape(&c.to_string()),
+        })
+        .collect()
+}
+
+/// Whether anything containing a match for `pattern` also contains a match for `previous`, given
+/// that `previous` is a valid regex. Appending letters only ever extends the last branch of a
+/// valid regex, and a word containing a branch plus some letters contains the branch. (That's not
+/// so for whole-word matching: `cats` doesn't match `cat`.)
+fn narrows(previous: &str, pattern: &str) -> bool {
+    pattern
+        .strip_prefix(previous)
+        .is_some_and(|added| !added.is_empty() && added.chars().all(char::is_alphanumeric))
+}
+
+fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    let regex = Regex::new(pattern);
+    match &regex {
+        Ok(_) => log::trace!("compiled regex {:?}", pattern),
+        Err(e) => log::debug!("invalid regex {:?}: {}", pattern, e),
+    }
+    regex
+}
+
+/// Check whether `word` is a rearrangement of the sorted `letters` plus `n_wildcards` arbitrary
+/// characters, by sorting the word and walking both sorted lists together.
+fn is_anagram(word: &str, letters: &[char], n_wildcards: usize) -> bool {
+    if word.chars().count() != letters.len() + n_wildcards {
+        return false;
+    }
+    let mut word_chars: Vec<char> = word.chars().collect();
+    word_chars.sort_unstable();
+    let mut letters = letters.iter().peekable();
+    for c in word_chars {
+        match letters.peek() {
+            Some(&&letter) if letter == c => {
+                letters.next();
+            }
+            // the word has nothing left that could match this letter
+            Some(&&letter) if letter < c => return false,
+            // anything else in the word is covered by a wildcard, which the length check
+            // guarantees there are enough of
+            _ => {}
+        }
+    }
+    letters.peek().is_none()
+}
+
+/// Split `word` into spans with the first match of `regex` picked out in `color`, or leave it as
+/// one plain span if there is no regex or it only matches the empty string.
+fn highlight_match<'a>(word: &'a str, regex: Option<&Regex>, style: Style) -> Spans<'a> {
+    match regex.and_then(|regex| regex.find(word)) {
+        Some(match_) if !match_.as_str().is_empty() => Spans::from(vec![
+            Span::raw(&word[..match_.start()]),
+            Span::styled(match_.as_str(), style.add_modifier(Modifier::BOLD)),
+            Span::raw(&word[match_.end()..]),
+        ]),
+        _ => Spans::from(word),
+    }
+}
+
+/// `spans` cut short to `width` characters, ending in `…` if anything was cut. The styles of
+/// the spans that are kept stay as they were, so a highlight survives as far as it's shown.
+fn ellipsize(spans: Spans<'_>, width: usize) -> Spans<'_> {
+    if spans_len(&spans) <= width {
+        return spans;
+    }
+    let ellipsis = if width > 1 { "…" } else { "" };
+    let mut room = width - ellipsis.chars().count();
+    let mut kept = vec![];
+    for span in spans.0 {
+        let len = span.content.chars().count();
+        if len <= room {
+            room -= len;
+            kept.push(span);
+            continue;
+        }
+        let end = span
+            .content
+            .char_indices()
+            .nth(room)
+            .map_or(span.content.len(), |(i, _)| i);
+        let content = match span.content {
+            Cow::Borrowed(s) => Cow::Borrowed(&s[..end]),
+            Cow::Owned(s) => Cow::Owned(s[..end].to_string()),
+        };
+        kept.push(Span::styled(content, span.style));
+        break;
+    }
+    kept.push(Span::raw(ellipsis));
+    Spans::from(kept)
+}
+
+/// How many characters `spans` takes up, counting each one as a single column.
+fn spans_len(spans: &Spans) -> usize {
+    spans
+        .0
+        .iter()
+        .map(|span| span.content.chars().count())
+        .sum()
+}
+
+/// The keyboard shortcuts for the interactive TUI.
+fn help_table(theme: &Theme) -> widgets::Table<'static> {
+    let key_style = theme.emphasized(theme.help_key);
+    widgets::Table::new(vec![
+        widgets::Row::new(vec![
+            widgets::Cell::from("<F1>").style(key_style),
+            widgets::Cell::from("Toggle this help"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("?").style(key_style),
+            widgets::Cell::from("Toggle help (empty input)"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("<Up>|<Down>").style(key_style),
+            widgets::Cell::from("Select a match"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("<Tab>|<S-Tab>").style(key_style),
+            widgets::Cell::from("Next or previous column"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("^D").style(key_style),
+            widgets::Cell::from("Next page"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("^U").style(key_style),
+            widgets::Cell::from("Previous page"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("^S").style(key_style),
+            widgets::Cell::from("Save matches to a file"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("<Enter>").style(key_style),
+            widgets::Cell::from("Add to the history"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("^P|^N").style(key_style),
+            widgets::Cell::from("Older or newer pattern"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("<Left>|<Right>").style(key_style),
+            widgets::Cell::from("Move the cursor"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("<Home>|<End>").style(key_style),
+            widgets::Cell::from("Start or end of the input"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("<BS>|<Del>").style(key_style),
+            widgets::Cell::from("Delete around the cursor"),
+        ]),
+        widgets::Row::new(vec![
+            widgets::Cell::from("<Esc>|^C").style(key_style),
+            widgets::Cell::from("Quit"),
+        ]),
+    ])
+    .widths(&[
+        layout::Constraint::Length(14),
+        layout::Constraint::Length(26),
+    ])
+    .block(theme.block().title("Help"))
+}
+
+/// A `width` by `height` rectangle centered in `area`, shrunk to fit if `area` is too small.
+fn centered_rect(width: u16, height: u16, area: layout::Rect) -> layout::Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    layout::Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// `n` of `words` in a random order, or all of them shuffled if there are no more than `n`. The
+/// same `seed` picks the same words from the same list.
+fn pick_random<'a>(words: &[&'a str], n: usize, seed: Option<u64>) -> Vec<&'a str> {
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut picked: Vec<&str> = words.choose_multiple(&mut rng, n).copied().collect();
+    // `choose_multiple` picks at random but doesn't promise to put them in a random order.
+    picked.shuffle(&mut rng);
+    picked
+}
+
+/// "1 match" or "N matches".
+fn count_matches(n: usize) -> String {
+    match n {
+        1 => "1 match".to_string(),
+        n => format!("{} matches", n),
+    }
+}
+
+/// The gist of why a regex didn't parse, on one line. A syntax error's message quotes the pattern
+/// with a caret under the problem, which only makes sense over several lines, so just its last
+/// line is kept.
+fn regex_error_summary(error: &regex::Error) -> String {
+    let message = error.to_string();
+    let last_line = message.lines().last().unwrap_or_default().trim();
+    format!(
+        "Invalid regex: {}",
+        last_line.strip_prefix("error: ").unwrap_or(last_line)
+    )
+}
+
+/// How many pages it takes to show `n_matches` words, `page_capacity` at a time. There is always
+/// at least one page, even if it's empty.
+fn n_pages(n_matches: usize, page_capacity: usize) -> usize {
+    if page_capacity == 0 {
+        1
+    } else {
+        n_matches.div_ceil(page_capacity).max(1)
+    }
+}
+
+fn transpose<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>>
+where
+    T: Clone,
+{
+    v.iter().fold(vec![], |acc, row| {
+        row.iter().enumerate().fold(acc, |mut acc, (i, cell)| {
+            if acc.len() <= i {
+                acc.push(vec![]);
+            }
+            acc[i].push(cell.clone());
+            acc
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regex_whole_word() {
+        let engine = MatchEngine::new("cat".to_string(), MatchOptions::default());
+        assert_eq!(engine.matches().unwrap(), vec!["cat"]);
+        let engine = MatchEngine::new("cat|dog".to_string(), MatchOptions::default());
+        assert_eq!(engine.matches().unwrap(), vec!["cat", "dog"]);
+    }
+
+    #[test]
+    fn test_regex_contains() {
+        let options = MatchOptions {
+            contains: true,
+            ..Default::default()
+        };
+        let engine = MatchEngine::new("cat".to_string(), options);
+        let matches = engine.matches().unwrap();
+        assert!(matches.contains(&"cat"));
+        assert!(matches.contains(&"category"));
+        assert!(matches.contains(&"ducat"));
+        assert!(!matches.contains(&"dog"));
+        // matches never span more than one word
+        let engine = MatchEngine::new(r"t\sc".to_string(), options);
+        assert!(engine
+            .matches()
+            .unwrap()
+            .iter()
+            .all(|word| !word.contains('\n')));
+    }
+
+    #[test]
+    fn test_narrowing_matches_full_scan() {
+        let options = MatchOptions {
+            contains: true,
+            ..Default::default()
+        };
+        let mut engine = MatchEngine::new(String::new(), options);
+        // Typed one key at a time, with a backspace, an invalid pattern on the way and an
+        // alternation that later letters only extend the last branch of.
+        let typed = [
+            "", "e", "en", "ent", "en", "en(", "en(t", "en(t)", "en(t)s", "a|b", "a|bc", "a|bcd",
+        ];
+        for pattern in typed {
+            engine.pattern = pattern.to_string();
+            let fresh = MatchEngine::new(pattern.to_string(), options);
+            match (engine.matches(), fresh.matches()) {
+                (Ok(narrowed), Ok(full)) => assert_eq!(narrowed, full, "pattern {:?}", pattern),
+                (narrowed, full) => assert!(narrowed.is_err() && full.is_err()),
+            }
+        }
+        assert!(narrows("en", "ent"));
+        assert!(narrows("a|b", "a|bc"));
+        assert!(!narrows("ent", "en"));
+        assert!(!narrows("en", "en"));
+        assert!(!narrows("en", "en*"));
+        assert!(!narrows("en", "en|x"));
+    }
+
+    #[test]
+    fn test_regex_ignore_case() {
+        let options = MatchOptions {
+            ignore_case: true,
+            ..Default::default()
+        };
+        let engine = MatchEngine::new("CAT".to_string(), options);
+        assert_eq!(engine.matches().unwrap(), vec!["cat"]);
+        let engine = MatchEngine::new("CAT".to_string(), MatchOptions::default());
+        assert!(engine.matches().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_custom_words() {
+        let words = Cow::Owned("alpha\nbeta\ngamma\n".to_string());
+        let engine = MatchEngine::new(".*a".to_string(), MatchOptions::default()).with_words(words);
+        assert_eq!(engine.matches().unwrap(), vec!["alpha", "beta", "gamma"]);
+    }
+
+    #[test]
+    fn test_read_pattern() {
+        let mut input = io::Cursor::new("^cat.*\r\nignored\n");
+        assert_eq!(read_pattern(&mut input), Ok("^cat.*".to_string()));
+        assert!(read_pattern(&mut io::Cursor::new("")).is_err());
+        assert!(read_pattern(&mut io::Cursor::new("\n")).is_err());
+        let command = WordCommand::try_parse_from(["word"]).unwrap();
+        assert_eq!(
+            command.pattern_from(None).unwrap_err(),
+            "a pattern is required unless --interactive is set; pass one or pipe one in"
+        );
+        let piped = &mut io::Cursor::new("c.t\n");
+        assert_eq!(command.pattern_from(Some(piped)).unwrap(), "c.t");
+        let command = WordCommand::try_parse_from(["word", "d.g"]).unwrap();
+        assert_eq!(command.pattern_from(None).unwrap(), "d.g");
+        let command = WordCommand::try_parse_from(["word", "--stdin"]).unwrap();
+        assert!(command.pattern_from(None).is_err());
+    }
+
+    #[test]
+    fn test_read_words() {
+        let mut input = io::Cursor::new("zebra\r\n\napple\nmango");
+        let words = read_words(&mut input).unwrap();
+        assert_eq!(words, "zebra\napple\nmango\n");
+        let engine =
+            MatchEngine::new(".*a.*".to_string(), MatchOptions::default()).with_words(words);
+        assert_eq!(engine.matches().unwrap(), vec!["zebra", "apple", "mango"]);
+        assert!(
+            WordCommand::try_parse_from(["word", "--stdin", "--dict", "words.txt", "a"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_load_words_crlf() {
+        let path = std::env::temp_dir().join(format!("kit-words-test-{}.txt", std::process::id()));
+        fs::write(&path, "alpha\r\nbeta\r\n").unwrap();
+        let words = load_words(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let engine =
+            MatchEngine::new("beta".to_string(), MatchOptions::default()).with_words(words);
+        assert_eq!(engine.matches().unwrap(), vec!["beta"]);
+        assert!(load_words(&path).is_err());
+    }
+
+    #[test]
+    fn test_builtin_dicts() {
+        let all: HashSet<&str> = WORDS.lines().collect();
+        for builtin in [Builtin::Common, Builtin::FiveLetter] {
+            assert!(builtin.words().lines().all(|word| all.contains(word)));
+        }
+        assert!(Builtin::FiveLetter
+            .words()
+            .lines()
+            .all(|word| word.len() == 5 && word.chars().all(|c| c.is_ascii_lowercase())));
+        let engine = MatchEngine::new("c.t".to_string(), MatchOptions::default())
+            .with_builtin(Builtin::Common);
+        assert_eq!(engine.matches().unwrap(), ["cat", "cut"]);
+        assert!(Builtin::list().starts_with("all            102217 words"));
+        let command = WordCommand::try_parse_from(["word", "--builtin", "5-letter", "x"]).unwrap();
+        assert_eq!(command.words().unwrap(), FIVE_LETTER_WORDS);
+        assert!(
+            WordCommand::try_parse_from(["word", "--builtin", "all", "--dict", "w", "x"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_merge_word_lists() {
+        let lists = [
+            "gamma\nalpha\nbeta\nalpha\n".to_string(),
+            "beta\ndelta\n\ngamma\n".to_string(),
+        ];
+        assert_eq!(
+            merge_word_lists(&lists, DictMode::Union),
+            "alpha\nbeta\ndelta\ngamma\n"
+        );
+        assert_eq!(
+            merge_word_lists(&lists, DictMode::Intersection),
+            "beta\ngamma\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_tsv_definitions() {
+        let definitions =
+            parse_tsv_definitions("cat\ta small feline\r\n\ndog\ta loyal canine\ncat\tpet\n")
+                .unwrap();
+        assert_eq!(definition(&definitions, "dog"), "a loyal canine");
+        assert_eq!(definition(&definitions, "cat"), "pet");
+        assert_eq!(definition(&definitions, "emu"), NO_DEFINITION);
+        assert_eq!(
+            parse_tsv_definitions("cat\tfeline\ndog\n"),
+            Err("line 2: expected `word<TAB>definition`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dots_to_regex() {
+        assert_eq!(dots_to_regex(".t.r"), r"\pLt\pLr");
+        assert_eq!(dots_to_regex("c_?"), r"c\pL\pL");
+        assert_eq!(dots_to_regex("a+b"), r"a\+b");
+        let engine = MatchEngine::new(dots_to_regex("c?t"), MatchOptions::default());
+        let matches = engine.matches().unwrap();
+        assert!(matches.contains(&"cat"));
+        assert!(matches.contains(&"cut"));
+        assert!(!matches.contains(&"cart"));
+        assert!(matches.iter().all(|word| word.len() == 3));
+    }
+
+    #[test]
+    fn test_write_matches() {
+        let write = |definitions, format| {
+            let mut buf = vec![];
+            write_matches(
+                &mut buf,
+                &["cat", "dog"],
+                definitions,
+                false,
+                None,
+                format,
+                None,
+            )
+            .unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+        let definitions = Definitions::from([("cat".to_string(), "a feline, small".to_string())]);
+        assert_eq!(write(None, OutputFormat::Txt), "cat\ndog\n");
+        assert_eq!(
+            write(Some(&definitions), OutputFormat::Txt),
+            "cat: a feline, small\ndog: (no definition)\n"
+        );
+        assert_eq!(write(None, OutputFormat::Csv), "word\ncat\ndog\n");
+        assert_eq!(
+            write(Some(&definitions), OutputFormat::Csv),
+            "word,definition\ncat,\"a feline, small\"\ndog,(no definition)\n"
+        );
+        assert_eq!(write(None, OutputFormat::Json), "[\"cat\",\"dog\"]\n");
+        assert_eq!(
+            write(Some(&definitions), OutputFormat::Json),
+            "[{\"word\":\"cat\",\"definition\":\"a feline, small\"},\
+             {\"word\":\"dog\",\"definition\":\"(no definition)\"}]\n"
+        );
+    }
+
+    #[test]
+    fn test_colored_matches() {
+        assert!(ColorWhen::Always.enabled(false));
+        assert!(!ColorWhen::Never.enabled(true));
+        assert!(!ColorWhen::Auto.enabled(false));
+        let definitions = Definitions::from([("cat".to_string(), "a feline".to_string())]);
+        let coloring = Coloring {
+            highlight: Some(Regex::new("a").unwrap()),
+        };
+        let mut buf = vec![];
+        write_matches(
+            &mut buf,
+            &["cat"],
+            Some(&definitions),
+            true,
+            None,
+            OutputFormat::Txt,
+            Some(&coloring),
+        )
+        .unwrap();
+        let colored = String::from_utf8(buf).unwrap();
+        assert_eq!(
+            colored,
+            format!(
+                "c{}t {}: {}\n",
+                "a".green().bold(),
+                "5".dim(),
+                "a feline".dim()
+            )
+        );
+        let whole = Coloring { highlight: None };
+        assert_eq!(whole.word("dog"), "dog".green().bold().to_string());
+    }
+
+    #[test]
+    fn test_scrabble_score() {
+        assert_eq!(scrabble_score("quiz"), 22);
+        assert_eq!(scrabble_score("Jazz"), 29);
+        assert_eq!(scrabble_score("cat"), 5);
+        assert_eq!(scrabble_score("café"), 8);
+        assert_eq!(scrabble_score(""), 0);
+        let write = |definitions, format| {
+            let mut buf = vec![];
+            write_matches(
+                &mut buf,
+                &["quiz", "cat"],
+                definitions,
+                true,
+                None,
+                format,
+                None,
+            )
+            .unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+        let definitions = Definitions::from([("cat".to_string(), "a feline".to_string())]);
+        assert_eq!(write(None, OutputFormat::Txt), "quiz 22\ncat 5\n");
+        assert_eq!(
+            write(Some(&definitions), OutputFormat::Txt),
+            "quiz 22: (no definition)\ncat 5: a feline\n"
+        );
+        assert_eq!(
+            write(Some(&definitions), OutputFormat::Csv),
+            "word,score,definition\nquiz,22,(no definition)\ncat,5,a feline\n"
+        );
+        assert_eq!(
+            write(None, OutputFormat::Json),
+            "[{\"word\":\"quiz\",\"score\":22},{\"word\":\"cat\",\"score\":5}]\n"
+        );
+    }
+
+    #[test]
+    fn test_input_editing() {
+        let mut input = Input::default();
+        for c in "cafe".chars() {
+            input.insert(c);
+        }
+        input.delete();
+        input.backspace();
+        input.insert('é');
+        input.cursor = 2;
+        input.insert('ñ');
+        assert_eq!((input.text.as_str(), input.cursor), ("cañfé", 3));
+        input.delete();
+        assert_eq!(input.text, "cañé");
+        input.cursor = 0;
+        input.backspace();
+        input.delete();
+        assert_eq!((input.text.as_str(), input.len()), ("añé", 3));
+    }
+
+    #[test]
+    fn test_anagram() {
+        let engine = MatchEngine::with_mode("listen".to_string(), MatchMode::Anagram);
+        let matches = engine.matches().unwrap();
+        assert!(matches.contains(&"silent"));
+        assert!(matches.contains(&"enlist"));
+        assert!(!matches.contains(&"listens"));
+        assert!(matches.iter().all(|word| word.len() == 6));
+        let engine = MatchEngine::with_mode("c?t".to_string(), MatchMode::Anagram);
+        let matches = engine.matches().unwrap();
+        assert!(matches.contains(&"cat"));
+        assert!(matches.contains(&"act"));
+        assert!(matches.contains(&"cut"));
+        assert!(!matches.contains(&"cab"));
+    }
+
+    #[test]
+    fn test_is_anagram_respects_multiplicity() {
+        assert!(is_anagram("deed", &['d', 'd', 'e', 'e'], 0));
+        assert!(!is_anagram("dead", &['d', 'd', 'e', 'e'], 0));
+        assert!(is_anagram("dead", &['d', 'd', 'e'], 1));
+    }
+
+    #[test]
+    fn test_buildable() {
+        let engine = MatchEngine::with_mode("tacos".to_string(), MatchMode::Buildable);
+        let matches = engine.matches().unwrap();
+        assert!(matches.contains(&"coast"));
+        assert!(matches.contains(&"cat"));
+        assert!(!matches.contains(&"toast"));
+        assert!(matches
+            .windows(2)
+            .all(|pair| pair[0].len() >= pair[1].len()));
+        let engine = MatchEngine::with_mode("".to_string(), MatchMode::Buildable);
+        assert!(engine.matches().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rhyme() {
+        let words = "bring\nbrink\nking\nMing\nring\nspring\nstring\nthing\nwing";
+        let rhymes = |min_len| {
+            MatchEngine::with_mode("String".to_string(), MatchMode::Rhyme(min_len))
+                .with_words(Cow::Borrowed(words))
+                .matches()
+                .unwrap()
+                .join(" ")
+        };
+        assert_eq!(rhymes(None), "bring ring spring");
+        assert_eq!(rhymes(Some(3)), "bring ring spring Ming king thing wing");
+        assert_eq!(shared_ending("THING", "wing"), 3);
+        assert_eq!(shared_ending("brink", "wing"), 0);
+    }
+
+    #[test]
+    fn test_combine_patterns() {
+        let words = "amazing\nbuzz\nsing\nzing\nzoo";
+        let combined = |combine, contains| {
+            let options = MatchOptions {
+                contains,
+                ..MatchOptions::default()
+            };
+            MatchEngine::new(".*z.*".to_string(), options)
+                .with_words(Cow::Borrowed(words))
+                .with_patterns(vec![".*ing".to_string()], combine)
+                .matches()
+                .unwrap()
+                .join(" ")
+        };
+        assert_eq!(combined(Combine::And, false), "amazing zing");
+        assert_eq!(combined(Combine::Or, false), "amazing buzz sing zing zoo");
+        assert_eq!(combined(Combine::And, true), "amazing zing");
+        let engine = MatchEngine::new(
+            "z".to_string(),
+            MatchOptions {
+                contains: true,
+                ignore_case: false,
+            },
+        )
+        .with_words(Cow::Borrowed(words))
+        .with_patterns(vec!["ng$".to_string(), "^s".to_string()], Combine::Or);
+        assert_eq!(
+            engine.matches().unwrap(),
+            ["amazing", "buzz", "sing", "zing", "zoo"]
+        );
+        let highlight = engine.highlight_regex().unwrap();
+        assert_eq!(highlight.find_iter("zing").count(), 2);
+        // The first --pattern stands in for a missing pattern argument.
+        let command =
+            WordCommand::try_parse_from(["word", "--pattern", "a.*", "--pattern", ".*z"]).unwrap();
+        assert_eq!(command.pattern_from(None).unwrap(), "a.*");
+        assert_eq!(command.extra_patterns(), [".*z"]);
+        let command = WordCommand::try_parse_from(["word", "b.*", "--pattern", ".*z"]).unwrap();
+        assert_eq!(command.extra_patterns(), [".*z"]);
+    }
+
+    #[test]
+    fn test_fuzzy() {
+        let words = "bard\ncard\ncare\ncart\ncarts\ncat\nCord\ndog\nscared";
+        let fuzzy = |max| {
+            MatchEngine::with_mode("card".to_string(), MatchMode::Fuzzy(max))
+                .with_words(Cow::Borrowed(words))
+                .matches()
+                .unwrap()
+                .join(" ")
+        };
+        assert_eq!(fuzzy(0), "card");
+        assert_eq!(fuzzy(1), "card Cord bard care cart");
+        assert_eq!(fuzzy(2), "card Cord bard care cart carts cat scared");
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(
+            bounded_edit_distance(&chars("kitten"), &chars("sitting"), 3),
+            Some(3)
+        );
+        assert_eq!(
+            bounded_edit_distance(&chars("kitten"), &chars("sitting"), 2),
+            None
+        );
+        assert_eq!(
+            bounded_edit_distance(&chars("café"), &chars("cafe"), 1),
+            Some(1)
+        );
+        assert_eq!(bounded_edit_distance(&chars("a"), &chars("abcd"), 2), None);
+        let mut buf = vec![];
+        write_matches(
+            &mut buf,
+            &["card", "cart"],
+            None,
+            false,
+            Some("card"),
+            OutputFormat::Csv,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "word,distance\ncard,0\ncart,1\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_order() {
+        let mut words = vec!["bb", "a", "ccc", "ab"];
+        SortOrder::Alpha.sort(&mut words);
+        assert_eq!(words, vec!["a", "ab", "bb", "ccc"]);
+        let mut words = vec!["bb", "a", "ccc", "ab"];
+        SortOrder::Length.sort(&mut words);
+        assert_eq!(words, vec!["a", "bb", "ab", "ccc"]);
+        let mut words = vec!["bb", "a", "ccc", "ab"];
+        SortOrder::LengthDesc.sort(&mut words);
+        assert_eq!(words, vec!["ccc", "bb", "ab", "a"]);
+        let mut words = vec!["bb", "a", "ccc", "ab"];
+        SortOrder::Score.sort(&mut words);
+        assert_eq!(words, vec!["ccc", "bb", "ab", "a"]);
+    }
+
+    #[test]
+    fn test_highlight_match() {
+        let regex = Regex::new("cat").unwrap();
+        let spans = highlight_match("ducats", Some(&regex), Style::default().fg(Color::Yellow));
+        let contents: Vec<&str> = spans.0.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(contents, vec!["du", "cat", "s"]);
+        assert_ne!(spans.0[1].style, Style::default());
+        let regex = Regex::new("x*").unwrap();
+        assert_eq!(
+            highlight_match("ducats", Some(&regex), Style::default().fg(Color::Yellow))
+                .0
+                .len(),
+            1
+        );
+        assert_eq!(
+            highlight_match("ducats", None, Style::default().fg(Color::Yellow))
+                .0
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_ellipsize() {
+        let contents = |spans: Spans| -> Vec<String> {
+            spans
+                .0
+                .iter()
+                .map(|span| span.content.to_string())
+                .collect()
+        };
+        let regex = Regex::new("cat").unwrap();
+        let style = Style::default().fg(Color::Yellow);
+        let spans = ellipsize(highlight_match("ducats", Some(&regex), style), 6);
+        assert_eq!(contents(spans), ["du", "cat", "s"]);
+        let spans = ellipsize(highlight_match("ducats", Some(&regex), style), 4);
+        assert_eq!(contents(spans.clone()), ["du", "c", "…"]);
+        assert_eq!(spans.0[1].style, style.add_modifier(Modifier::BOLD));
+        assert_eq!(contents(ellipsize(Spans::from("ducats"), 1)), ["d", ""]);
+        assert_eq!(contents(ellipsize(Spans::from("éclair"), 3)), ["éc", "…"]);
+    }
+
+    #[test]
+    fn test_small_terminal() {
+        let mut app = typed_in("caterpillars\ncat\n", "c.*");
+        let lines = render_sized(&mut app, 14, 12);
+        assert!(lines.iter().any(|line| line.contains("│caterpi…│")));
+        assert!(lines.iter().any(|line| line.contains("│cat     │")));
+        let lines = render_sized(&mut app, 40, 8);
+        assert!(lines.iter().any(|line| line.contains("Terminal too small")));
+    }
+
+    #[test]
+    fn test_n_pages() {
+        assert_eq!(n_pages(0, 10), 1);
+        assert_eq!(n_pages(1, 10), 1);
+        assert_eq!(n_pages(10, 10), 1);
+        assert_eq!(n_pages(11, 10), 2);
+        assert_eq!(n_pages(11, 0), 1);
+    }
+
+    #[test]
+    fn test_regex_error_summary() {
+        let error = MatchEngine::new("a(b".to_string(), MatchOptions::default())
+            .matches()
+            .unwrap_err();
+        assert_eq!(regex_error_summary(&error), "Invalid regex: unclosed group");
+        assert_eq!(count_matches(1), "1 match");
+        assert_eq!(count_matches(0), "0 matches");
+    }
+
+    /// Wait for the searcher to catch up with the input.
+    fn wait_for_search(app: &mut WordRegex) {
+        while app.pending.is_some() {
+            thread::sleep(Duration::from_millis(1));
+            app.receive();
+        }
+    }
+
+    /// A TUI searching `words`, with `pattern` typed in.
+    fn typed_in(words: &'static str, pattern: &str) -> WordRegex {
+        let mut app = WordRegex::new(
+            Cow::Borrowed(words),
+            MatchOptions::default(),
+            LengthBounds::default(),
+            None,
+            false,
+            Theme::default(),
+            None,
+        );
+        for c in pattern.chars() {
+            app.input.insert(c);
+        }
+        app.edited();
+        wait_for_search(&mut app);
+        app
+    }
+
+    /// The rows of a 40 by 12 terminal after drawing `app`.
+    fn render(app: &mut WordRegex) -> Vec<String> {
+        render_sized(app, 40, 12)
+    }
+
+    /// The rows of a `width` by `height` terminal after drawing `app`.
+    fn render_sized(app: &mut WordRegex, width: u16, height: u16) -> Vec<String> {
+        let backend = ratatui::backend::TestBackend::new(width, height);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|f| app.draw(f).unwrap()).unwrap();
+        crate::tui::drawn_lines(&terminal)
+    }
+
+    #[test]
+    fn test_draw_invalid_regex() {
+        let mut app = typed_in("ab\nabc\n", "ab.");
+        let lines = render(&mut app);
+        assert!(lines[6].contains("abc"));
+        assert!(lines[9].contains("1 match in"));
+        app.input.insert('(');
+        app.edited();
+        wait_for_search(&mut app);
+        let lines = render(&mut app);
+        assert!(lines[5].contains("Matches (invalid regex)"));
+        assert!(lines[6..8]
+            .iter()
+            .all(|line| line.trim_matches([' ', '│']).is_empty()));
+        assert!(lines[9].contains("Invalid regex: unclosed group"));
+    }
+
+    #[tokio::test]
+    async fn test_word_app_frames() {
+        let mut app = typed_in("cat\ncot\ndog\n", "");
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(60, 20)).unwrap();
+        let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        let events = vec![key('c'), key('.'), key('t')];
+        crate::tui::event_loop(
+            &mut app,
+            &mut terminal,
+            crate::tui::scripted(events, Duration::from_millis(50)),
+        )
+        .await
+        .unwrap();
+        let lines = crate::tui::drawn_lines(&terminal);
+        let has = |text: &str| lines.iter().any(|line| line.contains(text));
+        assert!(has("> c.t"));
+        assert!(has("Matches (2 total)"));
+        assert!(has("cat") && has("cot"));
+        assert!(!has("dog"));
+        assert!(has("2 matches in"));
+        let mut app = typed_in("cat\ncot\ndog\n", "");
+        let events = crate::tui::scripted(vec![key('?')], Duration::from_millis(50));
+        crate::tui::event_loop(&mut app, &mut terminal, events)
+            .await
+            .unwrap();
+        let lines = crate::tui::drawn_lines(&terminal);
+        let has = |text: &str| lines.iter().any(|line| line.contains(text));
+        assert!(has("Toggle help (empty input)"));
+        assert!(has("Select a match"));
+    }
+
+    #[test]
+    fn test_slow_search() {
+        let mut app = typed_in("ab\nabc\n", "ab.");
+        app.input.insert('*');
+        app.edited();
+        let started = Instant::now() - SEARCH_TIMEOUT;
+        app.pending = Some(("ab.*".to_string(), started));
+        assert!(app.needs_redraw());
+        let lines = render(&mut app);
+        assert!(lines[6].contains("abc"));
+        assert!(lines[9].contains("Pattern too slow"));
+        assert!(!app.needs_redraw());
+        wait_for_search(&mut app);
+        assert!(app.needs_redraw());
+        let lines = render(&mut app);
+        assert!(lines[6].contains("ab ") && lines[7].contains("abc"));
+        assert!(lines[9].contains("2 matches in"));
+    }
+
+    #[test]
+    fn test_pick_random() {
+        let words = ["act", "cat", "dog", "tack", "taco"];
+        let picked = pick_random(&words, 3, Some(7));
+        assert_eq!(picked.len(), 3);
+        assert!(picked.iter().all(|word| words.contains(word)));
+        assert_eq!(picked.iter().unique().count(), 3);
+        assert_eq!(pick_random(&words, 3, Some(7)), picked);
+        let mut all = pick_random(&words, 10, None);
+        all.sort();
+        assert_eq!(all, words);
+        assert!(pick_random(&[], 2, None).is_empty());
+    }
+
+    #[test]
+    fn test_length_bounds() {
+        let engine = MatchEngine::new("c.*".to_string(), MatchOptions::default())
+            .with_words("cab\ncat\ncatch\ncafé\ncatalog\ndog\n");
+        let filtered = |min, max| {
+            let mut matches = engine.matches().unwrap();
+            LengthBounds { min, max }.retain(&mut matches);
+            matches
+        };
+        assert_eq!(filtered(None, None).len(), 5);
+        assert_eq!(filtered(Some(4), None), ["catch", "café", "catalog"]);
+        assert_eq!(filtered(None, Some(4)), ["cab", "cat", "café"]);
+        assert_eq!(filtered(Some(4), Some(5)), ["catch", "café"]);
+        let engine = MatchEngine::with_mode("tac".to_string(), MatchMode::Buildable)
+            .with_words("a\nat\ncat\ntack\n");
+        let mut matches = engine.matches().unwrap();
+        LengthBounds {
+            min: Some(2),
+            max: None,
+        }
+        .retain(&mut matches);
+        assert_eq!(matches, ["cat", "at"]);
+        let command = WordCommand::parse_from(["word", "--min-len", "5", "--max-len", "3", "x"]);
+        assert!(command.lengths().is_err());
+    }
+
+    #[test]
+    fn test_prefix() {
+        let regex = |pattern: &str| {
+            MatchEngine::new(
+                format!("{}.*", regex::escape(pattern)),
+                MatchOptions::default(),
+            )
+            .matching_words()
+            .unwrap()
+        };
+        let engine = MatchEngine::with_mode("quiz".to_string(), MatchMode::Prefix);
+        assert_eq!(engine.matching_words().unwrap(), regex("quiz"));
+        assert!(engine.matches().unwrap().len() > 1);
+        for prefix in ["a", "zyz", "xylophone", "qqqq", "-"] {
+            let engine = MatchEngine::with_mode(prefix.to_string(), MatchMode::Prefix);
+            assert_eq!(
+                engine.matching_words().unwrap(),
+                regex(prefix),
+                "{:?}",
+                prefix
+            );
+        }
+        let engine = MatchEngine::with_mode("ca".to_string(), MatchMode::Prefix)
+            .with_words("cat\nbat\n\ncab\ncart\nCap\n");
+        assert_eq!(engine.matches().unwrap(), ["cab", "cart", "cat"]);
+    }
+
+    #[test]
+    fn test_transpose() {
+        assert_eq!(
+            transpose(vec![vec![1, 2, 3]]),
+            vec![vec![1], vec![2], vec![3]]
+        );
+        assert_eq!(
+            transpose(vec![vec![1, 2, 3], vec![4, 5, 6]]),
+            vec![vec![1, 4], vec![2, 5], vec![3, 6]]
+        );
+        assert_eq!(
+            transpose(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]),
+            vec![vec![1, 4, 7], vec![2, 5, 8], vec![3, 6, 9]]
+        );
+    }
+}
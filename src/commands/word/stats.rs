@@ -0,0 +1,116 @@
+use std::collections::{BTreeMap, HashMap};
+
+use itertools::Itertools;
+
+/// How many of the most common first letters to list
+const TOP_LETTERS: usize = 5;
+
+/// Counts over a word list, for `kit word --stats`.
+#[derive(Debug, PartialEq)]
+pub struct WordStats {
+    total: usize,
+    /// How many words there are of each length, in chars
+    lengths: BTreeMap<usize, usize>,
+    /// The most common first letters, ignoring case, and how many words start with each
+    first_letters: Vec<(char, usize)>,
+}
+
+pub fn compute(words: &str) -> WordStats {
+    let mut lengths = BTreeMap::new();
+    let mut first_letters = HashMap::new();
+    let mut total = 0;
+    for word in words.lines().filter(|word| !word.is_empty()) {
+        total += 1;
+        *lengths.entry(word.chars().count()).or_insert(0) += 1;
+        if let Some(first) = word.chars().next() {
+            for c in first.to_lowercase() {
+                *first_letters.entry(c).or_insert(0) += 1;
+            }
+        }
+    }
+    // Ties go alphabetically, so that the report doesn't change from run to run.
+    let first_letters = first_letters
+        .into_iter()
+        .sorted_by_key(|&(c, count)| (std::cmp::Reverse(count), c))
+        .take(TOP_LETTERS)
+        .collect();
+    WordStats {
+        total,
+        lengths,
+        first_letters,
+    }
+}
+
+impl WordStats {
+    /// The report to print. With a `width` to fill, each length also gets a bar, scaled so that
+    /// the most common length's bar reaches the edge; without one the counts stand alone.
+    pub fn report(&self, width: Option<usize>) -> String {
+        let mut report = format!("Words: {}\n", self.total);
+        if self.total > 0 {
+            let chars: usize = self.lengths.iter().map(|(len, n)| len * n).sum();
+            report += &format!("Average length: {:.1}\n", chars as f64 / self.total as f64);
+        }
+        let count_width = self.total.to_string().len().max("Words".len());
+        let most = self.lengths.values().copied().max().unwrap_or(0);
+        // The length, the count and the gaps around them take up this much of each row.
+        let bar_room = width.map(|width| width.saturating_sub(6 + 2 + count_width + 2).max(10));
+        report += &format!("\nLength  {:>count_width$}\n", "Words");
+        for (len, &count) in &self.lengths {
+            report += &format!("{:>6}  {:>count_width$}", len, count);
+            if let Some(room) = bar_room {
+                let bar = (count as f64 / most as f64 * room as f64).round() as usize;
+                report += &format!("  {}", "#".repeat(bar.max(1)));
+            }
+            report.push('\n');
+        }
+        if !self.first_letters.is_empty() {
+            let letters = self
+                .first_letters
+                .iter()
+                .map(|(c, count)| {
+                    format!("{} ({:.1}%)", c, *count as f64 / self.total as f64 * 100.0)
+                })
+                .join(", ");
+            report += &format!("\nMost common first letters: {}\n", letters);
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute() {
+        let stats = compute("apple\nApse\nbee\n\ncab\nçà\n");
+        assert_eq!(stats.total, 5);
+        assert_eq!(
+            stats.lengths,
+            BTreeMap::from([(2, 1), (3, 2), (4, 1), (5, 1)])
+        );
+        assert_eq!(
+            stats.first_letters,
+            [('a', 2), ('b', 1), ('c', 1), ('ç', 1)]
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let stats = compute("a\nbb\ncc\n");
+        assert_eq!(
+            stats.report(None),
+            "Words: 3\n\
+             Average length: 1.7\n\
+             \n\
+             Length  Words\n     \
+                  1      1\n     \
+                  2      2\n\
+             \n\
+             Most common first letters: a (33.3%), b (33.3%), c (33.3%)\n"
+        );
+        let bars = stats.report(Some(25));
+        assert!(bars.contains("     1      1  #####\n"), "{}", bars);
+        assert!(bars.contains("     2      2  ##########\n"), "{}", bars);
+    }
+}
@@ -0,0 +1,189 @@
+use clap::Parser;
+
+use super::{dots_to_regex, MatchEngine, MatchOptions};
+use crate::error::KitError;
+
+#[derive(Debug, Parser)]
+#[clap(about = "List the words a Wordle could still be, given the clues so far")]
+pub struct WordleCommand {
+    #[arg(
+        long,
+        help = "Letters known to be in place, with '.', '_' or '?' for the rest, e.g. `..a.t`",
+        value_name = "DOTS"
+    )]
+    green: Option<String>,
+
+    #[arg(
+        long,
+        help = "Letters in the word but not where they were guessed, as LETTER:POSITION pairs \
+                counting from 1, e.g. `e:1,r:4`",
+        value_name = "PAIRS",
+        value_parser = parse_yellows
+    )]
+    yellow: Option<Yellows>,
+
+    #[arg(
+        long,
+        help = "Letters not in the word; any that are also green or yellow are left out of this",
+        value_name = "LETTERS"
+    )]
+    grey: Option<String>,
+
+    #[arg(
+        long,
+        default_value_t = 5,
+        help = "How many letters the word has",
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    len: u64,
+}
+
+/// `--yellow` letters and where each was guessed, counting from 0
+type Yellows = Vec<(char, usize)>;
+
+impl WordleCommand {
+    pub fn run(&self) -> Result<(), KitError> {
+        let clues = self.clues().map_err(KitError::InvalidArgument)?;
+        for word in clues.candidates()? {
+            println!("{}", word);
+        }
+        Ok(())
+    }
+
+    fn clues(&self) -> Result<Clues, String> {
+        let len = self.len as usize;
+        let green = self.green.clone().unwrap_or_else(|| ".".repeat(len));
+        if green.chars().count() != len {
+            return Err(format!(
+                "--green {:?} has {} letters, but the word has {}",
+                green,
+                green.chars().count(),
+                len
+            ));
+        }
+        let yellows = self.yellow.clone().unwrap_or_default();
+        if let Some(&(letter, position)) = yellows.iter().find(|&&(_, position)| position >= len) {
+            return Err(format!(
+                "--yellow {}:{} is past the end of a {} letter word",
+                letter,
+                position + 1,
+                len
+            ));
+        }
+        Ok(Clues {
+            green: green.to_lowercase(),
+            yellows,
+            greys: self.grey.as_deref().unwrap_or_default().to_lowercase(),
+        })
+    }
+}
+
+fn parse_yellows(s: &str) -> Result<Yellows, String> {
+    s.split(',')
+        .map(|pair| {
+            let invalid = || format!("expected LETTER:POSITION, like `e:1`, got {:?}", pair);
+            let (letter, position) = pair.trim().split_once(':').ok_or_else(invalid)?;
+            let mut letters = letter.chars();
+            let (Some(letter), None) = (letters.next(), letters.next()) else {
+                return Err(invalid());
+            };
+            match position.parse::<usize>() {
+                Ok(position) if position > 0 && letter.is_alphabetic() => {
+                    Ok((letter.to_ascii_lowercase(), position - 1))
+                }
+                _ => Err(invalid()),
+            }
+        })
+        .collect()
+}
+
+/// What the guesses so far have shown about the word.
+#[derive(Debug)]
+struct Clues {
+    /// A `--pattern-dots` pattern for the green letters, which also fixes the length
+    green: String,
+    yellows: Yellows,
+    greys: String,
+}
+
+impl Clues {
+    /// The lowercase words fitting the clues, in dictionary order. The green letters and the
+    /// length go into a regex for `MatchEngine`, and the rest is checked word by word, since
+    /// "somewhere, but not here" and "nowhere" don't come out as a regex.
+    fn candidates(&self) -> Result<Vec<String>, regex::Error> {
+        let engine = MatchEngine::new(dots_to_regex(&self.green), MatchOptions::default());
+        Ok(engine
+            .matches()?
+            .into_iter()
+            .filter(|word| word.chars().all(|c| c.is_ascii_lowercase()))
+            .filter(|word| self.allows(word))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Whether `word`, which already fits the green letters, has every yellow letter but not
+    /// where it was guessed, and no grey letter. A grey letter that's green or yellow too only
+    /// says there isn't another one, which can't be told without where it was guessed.
+    fn allows(&self, word: &str) -> bool {
+        let letters: Vec<char> = word.chars().collect();
+        let yellow_ok = self
+            .yellows
+            .iter()
+            .all(|&(letter, position)| letters[position] != letter && letters.contains(&letter));
+        let known = |c: char| self.green.contains(c) || self.yellows.iter().any(|&(y, _)| y == c);
+        let grey_ok = self
+            .greys
+            .chars()
+            .filter(|&c| !known(c))
+            .all(|c| !letters.contains(&c));
+        yellow_ok && grey_ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_yellows() {
+        assert_eq!(parse_yellows("e:1, R:4"), Ok(vec![('e', 0), ('r', 3)]));
+        assert!(parse_yellows("e1").is_err());
+        assert!(parse_yellows("e:0").is_err());
+        assert!(parse_yellows("er:2").is_err());
+        assert!(parse_yellows("5:2").is_err());
+    }
+
+    #[test]
+    fn test_clues() {
+        let clues = |args: &[&str]| {
+            WordleCommand::try_parse_from(["wordle"].iter().chain(args).copied())
+                .unwrap()
+                .clues()
+        };
+        let found = clues(&["--green", "..a.t", "--yellow", "e:1,r:4", "--grey", "sol"])
+            .unwrap()
+            .candidates()
+            .unwrap();
+        assert!(found.contains(&"react".to_string()));
+        assert!(!found.contains(&"roast".to_string()));
+        assert!(found.iter().all(|word| {
+            let letters: Vec<char> = word.chars().collect();
+            letters.len() == 5
+                && letters[2] == 'a'
+                && letters[4] == 't'
+                && letters[0] != 'e'
+                && word.contains('e')
+                && letters[3] != 'r'
+                && word.contains('r')
+                && !word.contains(['s', 'o', 'l'])
+        }));
+        // The grey `t` is the green one, so it doesn't rule anything out.
+        let clues = clues(&["--green", "t....", "--grey", "t"]).unwrap();
+        assert!(clues.allows("tutor"));
+        let clues = WordleCommand::try_parse_from(["wordle", "--len", "4", "--green", "..a.t"])
+            .unwrap()
+            .clues();
+        assert!(clues.is_err());
+    }
+}
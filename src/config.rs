@@ -0,0 +1,172 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::KitError, theme::Theme};
+
+/// Settings read from the user's config file. Every key is optional; whatever is missing falls
+/// back to the defaults built into each command.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub pomo: PomoConfig,
+    pub theme: Theme,
+}
+
+/// The `[pomo]` section.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PomoConfig {
+    pub time: Option<u64>,
+    #[serde(rename = "break")]
+    pub break_: Option<u64>,
+    pub long_break: Option<u64>,
+    pub n_pomos: Option<u64>,
+    pub keys: PomoKeys,
+}
+
+/// The `[pomo]` section with nothing left unset: the settings a session runs with once flags,
+/// the config file and the defaults are all taken into account, as `--print-config` shows them.
+#[derive(Debug, Serialize)]
+pub struct ResolvedPomoConfig {
+    pub time: u64,
+    #[serde(rename = "break")]
+    pub break_: u64,
+    pub long_break: u64,
+    pub n_pomos: u64,
+    pub keys: PomoKeys,
+}
+
+impl ResolvedPomoConfig {
+    /// The settings as a `[pomo]` section, ready to paste into a config file.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        #[derive(Serialize)]
+        struct File<'a> {
+            pomo: &'a ResolvedPomoConfig,
+        }
+        toml::to_string(&File { pomo: self })
+    }
+}
+
+/// The `[pomo.keys]` section: keys for each action in the pomo TUI, like `pause = "p"` or
+/// `quit = ["q", "ctrl+d"]`. An action that is set loses its default keys.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PomoKeys {
+    pub pause: Option<KeySpecs>,
+    pub skip: Option<KeySpecs>,
+    /// Go back to the previous segment
+    pub previous: Option<KeySpecs>,
+    pub quit: Option<KeySpecs>,
+    pub help: Option<KeySpecs>,
+    /// Change what the gauge is labeled with
+    pub label: Option<KeySpecs>,
+    /// Show or hide when the segment and cycle end
+    pub end_times: Option<KeySpecs>,
+}
+
+/// One key spec or a list of them.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum KeySpecs {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpecs {
+    pub fn specs(&self) -> &[String] {
+        match self {
+            KeySpecs::One(spec) => std::slice::from_ref(spec),
+            KeySpecs::Many(specs) => specs,
+        }
+    }
+}
+
+impl Config {
+    /// The config file lives at `<config dir>/kit/config.toml`, e.g. `~/.config/kit/config.toml`
+    /// on Linux.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("kit").join("config.toml"))
+    }
+
+    /// Load the config from the default path, or an empty config if there is no such file.
+    pub fn load() -> Result<Self, KitError> {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, KitError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents).map_err(|e| {
+                KitError::Config(format!("invalid config file {}: {}", path.display(), e))
+            }),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(KitError::Config(format!(
+                "cannot read config file {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    fn parse(contents: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_partial_pomo_section() {
+        let config = Config::parse("[pomo]\ntime = 50\nbreak = 10\n").unwrap();
+        assert_eq!(config.pomo.time, Some(50));
+        assert_eq!(config.pomo.break_, Some(10));
+        assert_eq!(config.pomo.long_break, None);
+        assert_eq!(config.pomo.n_pomos, None);
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.pomo.time, None);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Config::parse("[pomo]\ntime = \"long\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_pomo_keys() {
+        let config =
+            Config::parse("[pomo.keys]\npause = \"p\"\nquit = [\"x\", \"ctrl+d\"]\n").unwrap();
+        let keys = config.pomo.keys;
+        assert_eq!(keys.pause.unwrap().specs(), ["p"]);
+        assert_eq!(keys.quit.unwrap().specs(), ["x", "ctrl+d"]);
+        assert_eq!(keys.skip, None);
+        let error = Config::parse("[pomo.keys]\nlap = \"l\"\n").unwrap_err();
+        assert!(error.to_string().contains("unknown field `lap`"));
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        let config =
+            Config::parse("[theme]\nhighlight = \"#005f87\"\nborder = \"gray\"\n").unwrap();
+        assert_eq!(config.theme.highlight, Color::Rgb(0x00, 0x5f, 0x87));
+        assert_eq!(config.theme.border, Color::Gray);
+        assert_eq!(config.theme.help_key, Theme::default().help_key);
+        let error = Config::parse("[theme]\ngauge = \"blurple\"\n").unwrap_err();
+        assert!(error.to_string().contains("gauge"));
+        assert!(error.to_string().contains("unknown color \"blurple\""));
+    }
+}
@@ -0,0 +1,141 @@
+//! Durations as people write them on the command line, like `25m` or `1h30m`.
+
+use std::{num::IntErrorKind, time::Duration};
+
+/// Why `parse_duration` couldn't make sense of its input.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum DurationParseError {
+    #[error("expected a duration like 10m, 1h30m or 90s, got nothing")]
+    Empty,
+    #[error("expected a duration like 10m, 1h30m or 90s, got {0:?}")]
+    Invalid(String),
+    #[error("{0:?} is too long a duration")]
+    TooLong(String),
+    #[error("expected a whole number of minutes, like 25 or 1h30m, got {0:?}")]
+    NotWholeMinutes(String),
+}
+
+/// Parse hours, minutes and seconds, each a whole number with `h`, `m` or `s` after it, like
+/// `1h30m`, `90s` or `1h 30m 15s`. Each unit can come only once, and in that order. A bare
+/// number is minutes, since that's what segment lengths are in everywhere else in kit. Zero is a
+/// duration like any other; commands that can't use it have to turn it away themselves.
+pub fn parse_duration(s: &str) -> Result<Duration, DurationParseError> {
+    let input = s.trim();
+    if input.is_empty() {
+        return Err(DurationParseError::Empty);
+    }
+    let invalid = || DurationParseError::Invalid(s.to_string());
+    let too_long = || DurationParseError::TooLong(s.to_string());
+    let number = |digits: &str| {
+        digits.parse::<u64>().map_err(|e| match e.kind() {
+            IntErrorKind::PosOverflow => too_long(),
+            _ => invalid(),
+        })
+    };
+    if input.bytes().all(|b| b.is_ascii_digit()) {
+        return number(input)?
+            .checked_mul(60)
+            .map(Duration::from_secs)
+            .ok_or_else(too_long);
+    }
+    let units = [('h', 3600), ('m', 60), ('s', 1)];
+    // Units left to use, which shrinks as each one is, to keep them in order.
+    let mut allowed = &units[..];
+    let mut secs = 0u64;
+    let mut rest = input;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let n = number(&rest[..digits])?;
+        let mut after = rest[digits..].chars();
+        let unit = after.next().ok_or_else(invalid)?;
+        let i = allowed
+            .iter()
+            .position(|&(name, _)| name == unit)
+            .ok_or_else(invalid)?;
+        secs = n
+            .checked_mul(allowed[i].1)
+            .and_then(|n| secs.checked_add(n))
+            .ok_or_else(too_long)?;
+        allowed = &allowed[i + 1..];
+        rest = after.as_str().trim_start();
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+/// A `parse_duration` duration as a whole number of minutes, for the lengths kit keeps in
+/// minutes, like segments and the pomo reminder. `1h30m` is 90, and `90s` is an error.
+pub fn parse_minutes(s: &str) -> Result<u64, DurationParseError> {
+    let secs = parse_duration(s)?.as_secs();
+    if secs % 60 != 0 {
+        return Err(DurationParseError::NotWholeMinutes(s.to_string()));
+    }
+    Ok(secs / 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        let secs = |n| Ok(Duration::from_secs(n));
+        assert_eq!(parse_duration("0"), secs(0));
+        assert_eq!(parse_duration("0s"), secs(0));
+        assert_eq!(parse_duration("25"), secs(1500));
+        assert_eq!(parse_duration("25m"), secs(1500));
+        assert_eq!(parse_duration("90s"), secs(90));
+        assert_eq!(parse_duration("1h"), secs(3600));
+        assert_eq!(parse_duration("1h30m"), secs(5400));
+        assert_eq!(parse_duration("1h30m15s"), secs(5415));
+        assert_eq!(parse_duration("1h15s"), secs(3615));
+        assert_eq!(parse_duration("  1h 30m\t"), secs(5400));
+        assert_eq!(parse_duration(" 10 "), secs(600));
+    }
+
+    #[test]
+    fn test_parse_duration_errors() {
+        assert_eq!(parse_duration(""), Err(DurationParseError::Empty));
+        assert_eq!(parse_duration("   "), Err(DurationParseError::Empty));
+        for bad in [
+            "m", "10x", "1h30", "ten", "1.5h", "-5", "30m1h", "1m1m", "h1", "1 h", "1hm",
+        ] {
+            assert_eq!(
+                parse_duration(bad),
+                Err(DurationParseError::Invalid(bad.to_string())),
+                "{:?}",
+                bad
+            );
+        }
+        let huge = "99999999999999999999";
+        assert_eq!(
+            parse_duration(huge),
+            Err(DurationParseError::TooLong(huge.to_string()))
+        );
+        assert!(matches!(
+            parse_duration("999999999999999999h"),
+            Err(DurationParseError::TooLong(_))
+        ));
+        assert_eq!(
+            parse_duration("ten").unwrap_err().to_string(),
+            "expected a duration like 10m, 1h30m or 90s, got \"ten\""
+        );
+    }
+
+    #[test]
+    fn test_parse_minutes() {
+        assert_eq!(parse_minutes("25"), Ok(25));
+        assert_eq!(parse_minutes("1h30m"), Ok(90));
+        assert_eq!(parse_minutes("120s"), Ok(2));
+        assert_eq!(parse_minutes("0"), Ok(0));
+        assert_eq!(
+            parse_minutes("90s"),
+            Err(DurationParseError::NotWholeMinutes("90s".to_string()))
+        );
+        assert_eq!(
+            parse_minutes("ten"),
+            Err(DurationParseError::Invalid("ten".to_string()))
+        );
+    }
+}
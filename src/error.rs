@@ -0,0 +1,92 @@
+use std::{io, path::Path};
+
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinError,
+};
+
+/// What can go wrong running a kit command. `kit_main` only prints it, but embedders can match
+/// on the kind of failure.
+#[derive(Debug, thiserror::Error)]
+pub enum KitError {
+    /// A `kit word` pattern that doesn't compile
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// There's no terminal for a TUI, or it couldn't be set up or restored
+    #[error("{0}")]
+    Terminal(String),
+    /// The config file can't be read or isn't valid
+    #[error("{0}")]
+    Config(String),
+    /// A sound file that can't be read or played
+    #[error("{0}")]
+    Sound(String),
+    /// Options or inputs that clap can't check on its own, like a word list that isn't there or
+    /// bounds that contradict each other
+    #[error("{0}")]
+    InvalidArgument(String),
+    /// A countdown task that stopped before it was done
+    #[error("the countdown stopped unexpectedly: {0}")]
+    Countdown(String),
+}
+
+impl KitError {
+    /// An IO error on `path`, with what was being done to it in front, like "cannot read word
+    /// list words.txt: ...". The kind is kept, so that it can still be told apart.
+    pub fn io_at(doing: &str, path: &Path, e: io::Error) -> Self {
+        KitError::Io(io::Error::new(
+            e.kind(),
+            format!("{} {}: {}", doing, path.display(), e),
+        ))
+    }
+}
+
+// The countdowns run as tasks that report back over channels, which only fail if the other end
+// has gone away, as when a task panics.
+impl<T: std::fmt::Debug> From<watch::error::SendError<T>> for KitError {
+    fn from(e: watch::error::SendError<T>) -> Self {
+        KitError::Countdown(e.to_string())
+    }
+}
+
+impl From<watch::error::RecvError> for KitError {
+    fn from(e: watch::error::RecvError) -> Self {
+        KitError::Countdown(e.to_string())
+    }
+}
+
+impl<T: std::fmt::Debug> From<mpsc::error::TrySendError<T>> for KitError {
+    fn from(e: mpsc::error::TrySendError<T>) -> Self {
+        KitError::Countdown(e.to_string())
+    }
+}
+
+impl From<JoinError> for KitError {
+    fn from(e: JoinError) -> Self {
+        KitError::Countdown(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_at() {
+        let e = KitError::io_at(
+            "cannot read word list",
+            Path::new("words.txt"),
+            io::Error::from(io::ErrorKind::NotFound),
+        );
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "cannot read word list words.txt: {}",
+                io::Error::from(io::ErrorKind::NotFound)
+            )
+        );
+        assert!(matches!(e, KitError::Io(e) if e.kind() == io::ErrorKind::NotFound));
+    }
+}
@@ -1,8 +1,22 @@
+//! The `kit` command line tools. Besides the CLI, whose entry point is `kit_main`, the word
+//! search behind `kit word` can be used on its own through `MatchEngine`.
+
 mod commands;
+mod config;
+mod csv;
+mod duration;
+mod error;
+mod sound;
+mod theme;
+mod tui;
 mod with_tui;
 
-use crate::commands::pomo::PomoCommand;
+use crate::commands::completions::CompletionsCommand;
+use crate::commands::man::ManCommand;
+use crate::commands::pomo::{AlarmCommand, PomoCommand, TimerCommand};
 use crate::commands::word::WordCommand;
+pub use crate::commands::word::{Builtin, Combine, MatchEngine, MatchMode, MatchOptions};
+pub use crate::error::KitError;
 use clap::{Parser, Subcommand};
 use std::error::Error;
 
@@ -12,7 +26,12 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Command>,
 
-    #[arg(short, long, action = clap::ArgAction::Count)]
+    #[arg(
+        short,
+        long,
+        action = clap::ArgAction::Count,
+        help = "Log to stderr: -d for info, -dd for debug, -ddd for trace messages"
+    )]
     debug: u8,
 }
 
@@ -21,23 +40,57 @@ enum Command {
     #[command(name = "pomo")]
     Pomo(PomoCommand),
 
+    #[command(name = "timer")]
+    Timer(TimerCommand),
+
+    #[command(name = "alarm")]
+    Alarm(AlarmCommand),
+
     #[command(name = "word")]
     Word(WordCommand),
+
+    #[command(name = "completions")]
+    Completions(CompletionsCommand),
+
+    #[command(name = "man", hide = true)]
+    Man(ManCommand),
 }
 
 impl Command {
-    async fn run(&self) -> Result<(), Box<dyn Error>> {
+    async fn run(&self) -> Result<(), KitError> {
         match self {
             Command::Pomo(pomo) => pomo.run().await,
+            Command::Timer(timer) => timer.run().await,
+            Command::Alarm(alarm) => alarm.run().await,
             Command::Word(word) => word.run().await,
+            Command::Completions(completions) => completions.run(),
+            Command::Man(man) => man.run(),
         }
     }
 }
 
+/// Log kit's own messages to stderr, more verbosely for each `-d`. The TUIs draw on stdout, so
+/// running with `2> kit.log` keeps the log from scribbling over the screen.
+fn init_logging(debug: u8) {
+    let level = match debug {
+        0 => log::LevelFilter::Warn,
+        1 => log::LevelFilter::Info,
+        2 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    };
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Warn)
+        .filter_module("kit", level)
+        .target(env_logger::Target::Stderr)
+        .init();
+}
+
 #[tokio::main]
 pub async fn kit_main() -> Result<(), Box<dyn Error>> {
-    match Cli::parse().command {
-        Some(command) => command.run().await,
+    let cli = Cli::parse();
+    init_logging(cli.debug);
+    match cli.command {
+        Some(command) => Ok(command.run().await?),
         None => Ok(()),
     }
 }
@@ -0,0 +1,111 @@
+use std::{
+    io::{stdout, Write},
+    path::Path,
+    sync::Arc,
+};
+
+#[cfg(feature = "sound")]
+use std::time::Duration;
+
+use crate::error::KitError;
+
+/// An audio file read into memory up front, so that a missing or undecodable file is reported
+/// before any timer starts rather than when the sound is first needed.
+#[derive(Clone, Debug)]
+pub struct Sound {
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    data: Arc<[u8]>,
+    /// From 0.0 for silence to 1.0 for the file's own volume
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    volume: f32,
+    /// How many times to play the file each time the sound is played
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    repeat: u32,
+}
+
+/// The silence between one play of a repeated sound and the next
+#[cfg(feature = "sound")]
+const REPEAT_GAP: Duration = Duration::from_millis(300);
+
+impl Sound {
+    #[cfg(feature = "sound")]
+    pub fn load(path: &Path) -> Result<Self, KitError> {
+        let data: Arc<[u8]> = std::fs::read(path)
+            .map_err(|e| {
+                KitError::Sound(format!("cannot read sound file {}: {}", path.display(), e))
+            })?
+            .into();
+        rodio::Decoder::new(std::io::Cursor::new(data.clone())).map_err(|e| {
+            KitError::Sound(format!(
+                "cannot decode sound file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(Self {
+            data,
+            volume: 1.0,
+            repeat: 1,
+        })
+    }
+
+    #[cfg(not(feature = "sound"))]
+    pub fn load(path: &Path) -> Result<Self, KitError> {
+        Err(KitError::Sound(format!(
+            "cannot play {}: kit was built without the `sound` feature",
+            path.display()
+        )))
+    }
+
+    /// Play at `volume` percent, `repeat` times in a row.
+    pub fn with_playback(self, volume: u8, repeat: u32) -> Self {
+        Self {
+            volume: f32::from(volume) / 100.0,
+            repeat,
+            ..self
+        }
+    }
+
+    /// Play the sound on the default output device, blocking until it finishes.
+    #[cfg(feature = "sound")]
+    pub fn play(&self) -> Result<(), KitError> {
+        let unplayable =
+            |e: &dyn std::fmt::Display| KitError::Sound(format!("cannot play sound: {}", e));
+        let (_stream, handle) = rodio::OutputStream::try_default().map_err(|e| unplayable(&e))?;
+        let sink = rodio::Sink::try_new(&handle).map_err(|e| unplayable(&e))?;
+        sink.set_volume(self.volume);
+        for i in 0..self.repeat {
+            if i > 0 {
+                std::thread::sleep(REPEAT_GAP);
+            }
+            sink.append(
+                rodio::Decoder::new(std::io::Cursor::new(self.data.clone()))
+                    .map_err(|e| unplayable(&e))?,
+            );
+            sink.sleep_until_end();
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "sound"))]
+    pub fn play(&self) -> Result<(), KitError> {
+        Ok(())
+    }
+
+    /// Play the sound on a blocking tokio task so the caller's event loop keeps running.
+    pub fn spawn_play(&self) {
+        let sound = self.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = sound.play() {
+                log::debug!("failed to play sound: {}", e);
+            }
+        });
+    }
+}
+
+/// Ring the terminal bell.
+pub fn bell() -> std::io::Result<()> {
+    let mut stdout = stdout();
+    stdout.write_all(b"\x07")?;
+    stdout.flush()
+}
@@ -0,0 +1,363 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Widget},
+};
+use serde::{Deserialize, Deserializer};
+
+/// Colors for the TUIs, read from the `[theme]` section of the config file. Each value is a color
+/// name like `green` or `light-blue`, or a hex code like `#88c0d0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// The pomo gauge. When unset it shades from green to red as a work segment runs out, and
+    /// from blue to green over a break.
+    #[serde(deserialize_with = "deserialize_some_color")]
+    pub gauge: Option<Color>,
+    /// The pomo gauge while paused, if it should differ from `gauge`
+    #[serde(deserialize_with = "deserialize_some_color")]
+    pub gauge_paused: Option<Color>,
+    /// Behind the unfilled part of the pomo gauge, if not the terminal's background
+    #[serde(deserialize_with = "deserialize_some_color")]
+    pub gauge_background: Option<Color>,
+    /// The current pomo segment, and the matched part of each word in `kit word -ic`
+    #[serde(deserialize_with = "deserialize_color")]
+    pub highlight: Color,
+    /// Keys listed in the help tables
+    #[serde(deserialize_with = "deserialize_color")]
+    pub help_key: Color,
+    /// The check mark next to pomo segments that ran to completion
+    #[serde(deserialize_with = "deserialize_color")]
+    pub done: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub border: Color,
+    /// Added to the highlight, help keys and check marks, for presets that set them apart with
+    /// bold or reverse video rather than color
+    #[serde(skip)]
+    pub emphasis: Modifier,
+    /// Draw borders, gauges and marks with plain ASCII, for terminals that garble box-drawing
+    /// characters. Also turned on by `--ascii-only`, and for a `TERM` of `dumb` or `vt*`.
+    pub ascii_only: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Theme {
+    pub const DEFAULT: Theme = Theme {
+        gauge: None,
+        gauge_paused: None,
+        gauge_background: None,
+        highlight: Color::Green,
+        help_key: Color::Yellow,
+        done: Color::Green,
+        border: Color::Reset,
+        emphasis: Modifier::empty(),
+        ascii_only: false,
+    };
+
+    /// The accent colors of Solarized, for terminals set up with its palette or true color.
+    pub const SOLARIZED: Theme = Theme {
+        gauge: Some(Color::Rgb(0x26, 0x8b, 0xd2)),
+        gauge_paused: Some(Color::Rgb(0x58, 0x6e, 0x75)),
+        gauge_background: None,
+        highlight: Color::Rgb(0xb5, 0x89, 0x00),
+        help_key: Color::Rgb(0x2a, 0xa1, 0x98),
+        done: Color::Rgb(0x85, 0x99, 0x00),
+        border: Color::Rgb(0x58, 0x6e, 0x75),
+        emphasis: Modifier::empty(),
+        ascii_only: false,
+    };
+
+    /// The terminal's own colors throughout, as for `--no-color`.
+    pub const MONO: Theme = Theme {
+        gauge: Some(Color::Reset),
+        gauge_paused: None,
+        gauge_background: None,
+        highlight: Color::Reset,
+        help_key: Color::Reset,
+        done: Color::Reset,
+        border: Color::Reset,
+        emphasis: Modifier::empty(),
+        ascii_only: false,
+    };
+
+    /// No hues at all: a white-on-black gauge, and bold reverse video wherever the other themes
+    /// use an accent color.
+    pub const HIGH_CONTRAST: Theme = Theme {
+        gauge: Some(Color::White),
+        gauge_paused: None,
+        gauge_background: Some(Color::Black),
+        highlight: Color::Reset,
+        help_key: Color::Reset,
+        done: Color::Reset,
+        border: Color::Reset,
+        emphasis: Modifier::BOLD.union(Modifier::REVERSED),
+        ascii_only: false,
+    };
+
+    /// The built-in themes that `--theme` picks from, by name.
+    pub const PRESETS: [(&'static str, Theme); 4] = [
+        ("default", Theme::DEFAULT),
+        ("solarized", Theme::SOLARIZED),
+        ("mono", Theme::MONO),
+        ("high-contrast", Theme::HIGH_CONTRAST),
+    ];
+
+    /// The terminal's own colors throughout, for `--no-color`.
+    pub fn monochrome() -> Self {
+        Self::MONO
+    }
+
+    /// `preset` if one was picked with `--theme`, in place of this theme from the config file.
+    pub fn overridden_by(self, preset: Option<Theme>) -> Self {
+        preset.unwrap_or(self)
+    }
+
+    /// This theme, switched to ASCII if `ascii_only` is passed or `TERM` names a terminal that
+    /// can't be trusted with anything else.
+    pub fn with_ascii_only(self, ascii_only: bool) -> Self {
+        let term = std::env::var("TERM").unwrap_or_default();
+        Self {
+            ascii_only: self.ascii_only || ascii_only || term == "dumb" || term.starts_with("vt"),
+            ..self
+        }
+    }
+
+    /// This theme with its gauge and border colors dimmed by `factor`, as by `dim`.
+    pub fn dimmed(self, factor: f64) -> Self {
+        Self {
+            gauge: self.gauge.map(|color| dim(color, factor)),
+            gauge_paused: self.gauge_paused.map(|color| dim(color, factor)),
+            gauge_background: self.gauge_background.map(|color| dim(color, factor)),
+            border: dim(self.border, factor),
+            ..self
+        }
+    }
+
+    /// `color` with the theme's emphasis on top.
+    pub fn emphasized(&self, color: Color) -> Style {
+        Style::default().fg(color).add_modifier(self.emphasis)
+    }
+
+    /// A bordered block in the theme's border color.
+    pub fn block(&self) -> Block<'static> {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.border))
+    }
+}
+
+/// Rewrites whatever has been drawn to an area in ASCII: box-drawing lines become `-`, `|` and
+/// `+`, block elements become `#`, check marks become `*` and `x`, and an ellipsis becomes `~`.
+/// Rendering it last over the whole frame lets the widgets themselves stay unaware of
+/// `ascii_only`. Other characters, like the accents in dictionary words, are left for the
+/// terminal to cope with.
+pub struct AsciiOnly;
+
+impl Widget for AsciiOnly {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                let mut chars = cell.symbol.chars();
+                if let (Some(c), None) = (chars.next(), chars.next()) {
+                    if let Some(ascii) = ascii_for(c) {
+                        cell.set_char(ascii);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn ascii_for(c: char) -> Option<char> {
+    match c {
+        '─' | '━' | '═' | '╌' | '╍' | '┄' | '┅' | '┈' | '┉' => Some('-'),
+        '│' | '┃' | '║' | '╎' | '╏' | '┆' | '┇' | '┊' | '┋' => Some('|'),
+        '\u{2500}'..='\u{257f}' => Some('+'),
+        '\u{2580}'..='\u{259f}' => Some('#'),
+        '✓' | '✔' => Some('*'),
+        '✗' | '✘' => Some('x'),
+        '…' => Some('~'),
+        _ => None,
+    }
+}
+
+/// How bright `--auto-dim` leaves the colors at `hour` of the local day, from 1 for as they are
+/// down to 0.7 overnight. The day runs from 7 to 6 in the evening, and the colors fade over the
+/// hours either side of it.
+pub fn dim_factor(hour: u32) -> f64 {
+    match hour {
+        7..=17 => 1.0,
+        18 => 0.9,
+        6 | 19 => 0.8,
+        _ => 0.7,
+    }
+}
+
+/// `color` darkened to `factor` of its brightness, with blue taken down further than red and
+/// green so that it also gets warmer. Named colors go by xterm's palette. `Reset` and indexed
+/// colors are left alone, since what they look like is up to the terminal.
+pub fn dim(color: Color, factor: f64) -> Color {
+    if factor >= 1.0 {
+        return color;
+    }
+    let (r, g, b) = match color {
+        Color::Black => (0, 0, 0),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Blue => (0, 0, 238),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::Gray => (229, 229, 229),
+        Color::DarkGray => (127, 127, 127),
+        Color::LightRed => (255, 0, 0),
+        Color::LightGreen => (0, 255, 0),
+        Color::LightYellow => (255, 255, 0),
+        Color::LightBlue => (92, 92, 255),
+        Color::LightMagenta => (255, 0, 255),
+        Color::LightCyan => (0, 255, 255),
+        Color::White => (255, 255, 255),
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Reset | Color::Indexed(_) => return color,
+    };
+    let scale = |channel: u8, factor: f64| (channel as f64 * factor).round() as u8;
+    Color::Rgb(
+        scale(r, factor),
+        scale(g, factor),
+        scale(b, factor * factor),
+    )
+}
+
+/// Look up a `--theme` preset by name.
+pub fn parse_preset(name: &str) -> Result<Theme, String> {
+    Theme::PRESETS
+        .iter()
+        .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+        .map(|(_, theme)| *theme)
+        .ok_or_else(|| {
+            let names: Vec<&str> = Theme::PRESETS.iter().map(|(name, _)| *name).collect();
+            format!(
+                "unknown theme {:?}; expected one of: {}",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// Parse a color name, ignoring case and any `-`, `_` or spaces, or a `#rrggbb` hex code.
+fn parse_color(s: &str) -> Result<Color, String> {
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+            return Ok(Color::Rgb(channel(0), channel(2), channel(4)));
+        }
+        return Err(format!("invalid color {:?}: expected `#rrggbb`", s));
+    }
+    let name: String = s
+        .chars()
+        .filter(|c| !matches!(c, '-' | '_' | ' '))
+        .collect::<String>()
+        .to_lowercase();
+    match name.as_str() {
+        "reset" | "default" => Ok(Color::Reset),
+        "black" => Ok(Color::Black),
+        "red" => Ok(Color::Red),
+        "green" => Ok(Color::Green),
+        "yellow" => Ok(Color::Yellow),
+        "blue" => Ok(Color::Blue),
+        "magenta" => Ok(Color::Magenta),
+        "cyan" => Ok(Color::Cyan),
+        "gray" | "grey" => Ok(Color::Gray),
+        "darkgray" | "darkgrey" => Ok(Color::DarkGray),
+        "lightred" => Ok(Color::LightRed),
+        "lightgreen" => Ok(Color::LightGreen),
+        "lightyellow" => Ok(Color::LightYellow),
+        "lightblue" => Ok(Color::LightBlue),
+        "lightmagenta" => Ok(Color::LightMagenta),
+        "lightcyan" => Ok(Color::LightCyan),
+        "white" => Ok(Color::White),
+        _ => Err(format!("unknown color {:?}", s)),
+    }
+}
+
+fn deserialize_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_color(&s).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_some_color<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Color>, D::Error> {
+    deserialize_color(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("green"), Ok(Color::Green));
+        assert_eq!(parse_color("Light-Blue"), Ok(Color::LightBlue));
+        assert_eq!(parse_color("dark_grey"), Ok(Color::DarkGray));
+        assert_eq!(parse_color("#88C0d0"), Ok(Color::Rgb(0x88, 0xc0, 0xd0)));
+        assert!(parse_color("#88c0d").is_err());
+        assert!(parse_color("chartreuse").is_err());
+    }
+
+    #[test]
+    fn test_parse_preset() {
+        assert_eq!(parse_preset("solarized"), Ok(Theme::SOLARIZED));
+        assert_eq!(parse_preset("High-Contrast"), Ok(Theme::HIGH_CONTRAST));
+        assert_eq!(
+            parse_preset("nord"),
+            Err(
+                "unknown theme \"nord\"; expected one of: default, solarized, mono, high-contrast"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_dim() {
+        assert_eq!(dim(Color::Green, 1.0), Color::Green);
+        assert_eq!(dim(Color::Green, 0.5), Color::Rgb(0, 103, 0));
+        assert_eq!(
+            dim(Color::Rgb(200, 200, 200), 0.5),
+            Color::Rgb(100, 100, 50)
+        );
+        assert_eq!(dim(Color::Reset, 0.5), Color::Reset);
+        assert_eq!(dim(Color::Indexed(42), 0.5), Color::Indexed(42));
+        assert_eq!(dim_factor(12), 1.0);
+        assert!(dim_factor(19) < dim_factor(18));
+        assert_eq!(dim_factor(23), dim_factor(3));
+        let dimmed = Theme::SOLARIZED.dimmed(0.7);
+        assert_ne!(dimmed.gauge, Theme::SOLARIZED.gauge);
+        assert_ne!(dimmed.border, Theme::SOLARIZED.border);
+        assert_eq!(dimmed.highlight, Theme::SOLARIZED.highlight);
+        assert_eq!(Theme::MONO.dimmed(0.7), Theme::MONO);
+    }
+
+    #[test]
+    fn test_ascii_only() {
+        let area = Rect::new(0, 0, 8, 3);
+        let mut buf = Buffer::empty(area);
+        Theme::default()
+            .block()
+            .title("ok ✓")
+            .render(area, &mut buf);
+        buf.set_string(1, 1, "█▌é…", Style::default());
+        AsciiOnly.render(area, &mut buf);
+        let rows: Vec<String> = (0..3)
+            .map(|y| (0..8).map(|x| buf.get(x, y).symbol.as_str()).collect())
+            .collect();
+        assert_eq!(rows, ["+ok *--+", "|##é~  |", "+------+"]);
+    }
+}
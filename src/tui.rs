@@ -0,0 +1,410 @@
+use std::{io, ops::ControlFlow, time::Duration};
+
+use crossterm::{
+    event::{EnableMouseCapture, Event, EventStream, KeyCode, KeyEvent, KeyModifiers},
+    terminal::SetTitle,
+    ExecutableCommand,
+};
+use futures::{Stream, StreamExt};
+use ratatui::{
+    backend::{Backend, CrosstermBackend, TestBackend},
+    Frame, Terminal,
+};
+use tokio::time::{self, Interval, MissedTickBehavior};
+
+use crate::{
+    error::KitError,
+    theme::AsciiOnly,
+    with_tui::{self, WithTui},
+};
+
+/// A full-screen program for `run_app` to drive: it draws a frame, reacts to terminal events and,
+/// if it has a `tick_rate`, gets ticked in between. The screen is redrawn after every event, and
+/// after every tick that `needs_redraw`.
+pub trait App: WithTui {
+    /// Draw the current state. This takes `&mut self` so that an app can remember things that
+    /// depend on the layout, like how many rows fit or where it put its buttons.
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>) -> Result<(), KitError>;
+
+    fn handle_event(&mut self, event: Event) -> Result<ControlFlow<()>, KitError>;
+
+    fn tick(&mut self) -> Result<ControlFlow<()>, KitError> {
+        Ok(ControlFlow::Continue(()))
+    }
+
+    /// Called when the process gets SIGINT, which by default ends the loop. In raw mode Ctrl-C
+    /// arrives as a key event instead, so this is for signals sent by something else.
+    fn interrupt(&mut self) -> Result<ControlFlow<()>, KitError> {
+        Ok(ControlFlow::Break(()))
+    }
+
+    /// Called before the process stops for Ctrl-Z or SIGTSTP. Time goes on while it is stopped,
+    /// so an app that shouldn't count it can pause here, and carry on in `resume`.
+    fn suspend(&mut self) -> Result<(), KitError> {
+        Ok(())
+    }
+
+    /// Called once the process is continued after `suspend`, before the screen is redrawn.
+    fn resume(&mut self) -> Result<(), KitError> {
+        Ok(())
+    }
+
+    /// Whether the last tick changed what `draw` would show. Apps that tick more often than their
+    /// display changes can say no, to save redrawing the same thing.
+    fn needs_redraw(&self) -> bool {
+        true
+    }
+
+    /// How often to call `tick`, or `None` to only wake up for events.
+    fn tick_rate(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Whether to turn on mouse capture. It is opt-in because it stops the terminal from
+    /// selecting text.
+    fn captures_mouse(&self) -> bool {
+        false
+    }
+
+    /// Whether to redraw each frame in ASCII once the app has drawn it.
+    fn ascii_only(&self) -> bool {
+        false
+    }
+
+    /// What to call the terminal window after each draw, if anything.
+    fn title(&self) -> Option<String> {
+        None
+    }
+}
+
+/// What `event_loop` needs from a backend besides drawing: showing an app's `title`, and giving
+/// the terminal back to the shell while the process is stopped. Keeping these out of `App` lets
+/// an app be drawn to a `TestBackend` without touching the real terminal.
+pub trait AppBackend: Backend {
+    fn set_title(&mut self, title: &str) -> io::Result<()>;
+
+    /// Restore the terminal, stop the process, and take the terminal over again once it is
+    /// continued. `mouse` says whether to turn mouse capture back on.
+    fn suspend(&mut self, mouse: bool) -> Result<(), KitError>;
+}
+
+impl<W: io::Write> AppBackend for CrosstermBackend<W> {
+    fn set_title(&mut self, title: &str) -> io::Result<()> {
+        self.execute(SetTitle(title))?;
+        Ok(())
+    }
+
+    fn suspend(&mut self, mouse: bool) -> Result<(), KitError> {
+        with_tui::suspend(mouse)
+    }
+}
+
+impl AppBackend for TestBackend {
+    fn set_title(&mut self, _title: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn suspend(&mut self, _mouse: bool) -> Result<(), KitError> {
+        Ok(())
+    }
+}
+
+/// Take over the terminal and run `app` until it breaks out of the loop, then restore the terminal.
+pub async fn run_app<A: App>(app: &mut A) -> Result<(), KitError> {
+    let mut terminal = app.tui_setup()?;
+    if app.captures_mouse() {
+        io::stdout().execute(EnableMouseCapture)?;
+    }
+    let result = event_loop(app, &mut *terminal, EventStream::new()).await;
+    let shutdown = app.tui_shutdown(&mut terminal);
+    result.and(shutdown)
+}
+
+/// Draw `app`, then wait for the next tick, event or SIGINT and hand it over, until the app breaks
+/// or `events` runs out. Since the loop returns instead of the process being killed, `run_app`
+/// always gets to restore the terminal. Ctrl-Z and SIGTSTP suspend the app rather than reaching
+/// it, and SIGCONT redraws the whole screen in case the terminal was used in the meantime.
+pub async fn event_loop<A: App, B: AppBackend>(
+    app: &mut A,
+    terminal: &mut Terminal<B>,
+    mut events: impl Stream<Item = io::Result<Event>> + Unpin,
+) -> Result<(), KitError> {
+    let mut ticks = app.tick_rate().map(|rate| {
+        let mut ticks = time::interval(rate);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        ticks
+    });
+    let interrupted = tokio::signal::ctrl_c();
+    tokio::pin!(interrupted);
+    let mut job_control = JobControl::new()?;
+    let mut redraw = true;
+    loop {
+        if redraw {
+            let mut drawn = Ok(());
+            terminal.draw(|f| {
+                drawn = app.draw(f);
+                if app.ascii_only() {
+                    f.render_widget(AsciiOnly, f.size());
+                }
+            })?;
+            drawn?;
+            if let Some(title) = app.title() {
+                terminal.backend_mut().set_title(&title)?;
+            }
+        }
+        redraw = true;
+        let flow = tokio::select! {
+            _ = next_tick(&mut ticks) => {
+                let flow = app.tick()?;
+                redraw = app.needs_redraw();
+                flow
+            }
+            signal = &mut interrupted => {
+                signal?;
+                log::info!("interrupted");
+                interrupted.set(tokio::signal::ctrl_c());
+                app.interrupt()?
+            }
+            signal = job_control.recv() => {
+                match signal {
+                    JobSignal::Stop => suspend(app, terminal)?,
+                    JobSignal::Continue => terminal.clear()?,
+                }
+                ControlFlow::Continue(())
+            }
+            maybe_event = events.next() => match maybe_event {
+                Some(Ok(event)) if is_ctrl_z(&event) => {
+                    suspend(app, terminal)?;
+                    ControlFlow::Continue(())
+                }
+                Some(Ok(event)) => app.handle_event(event)?,
+                Some(Err(e)) => return Err(e.into()),
+                None => return Ok(()),
+            },
+        };
+        if flow.is_break() {
+            return Ok(());
+        }
+    }
+}
+
+/// Ctrl-Z, on the platforms where it suspends a process.
+fn is_ctrl_z(event: &Event) -> bool {
+    cfg!(unix)
+        && matches!(
+            event,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('z'),
+                modifiers,
+            }) if modifiers.contains(KeyModifiers::CONTROL)
+        )
+}
+
+/// Let `app` know it's being suspended, stop until continued, then clear the screen so that the
+/// next draw puts all of it back.
+fn suspend<A: App, B: AppBackend>(app: &mut A, terminal: &mut Terminal<B>) -> Result<(), KitError> {
+    log::info!("suspending");
+    app.suspend()?;
+    terminal.backend_mut().suspend(app.captures_mouse())?;
+    log::info!("continued");
+    terminal.clear()?;
+    app.resume()
+}
+
+enum JobSignal {
+    Stop,
+    Continue,
+}
+
+/// SIGTSTP and SIGCONT, on platforms that have them. In raw mode Ctrl-Z is a key press, so
+/// SIGTSTP only comes from something like `kill`; SIGCONT comes after any stop, even one that
+/// couldn't be caught, like SIGSTOP.
+struct JobControl {
+    #[cfg(unix)]
+    stop: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    cont: tokio::signal::unix::Signal,
+}
+
+impl JobControl {
+    #[cfg(unix)]
+    fn new() -> io::Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self {
+            stop: signal(SignalKind::from_raw(libc::SIGTSTP))?,
+            cont: signal(SignalKind::from_raw(libc::SIGCONT))?,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> io::Result<Self> {
+        Ok(Self {})
+    }
+
+    #[cfg(unix)]
+    async fn recv(&mut self) -> JobSignal {
+        tokio::select! {
+            _ = self.stop.recv() => JobSignal::Stop,
+            _ = self.cont.recv() => JobSignal::Continue,
+        }
+    }
+
+    #[cfg(not(unix))]
+    async fn recv(&mut self) -> JobSignal {
+        futures::future::pending().await
+    }
+}
+
+/// `events` one at a time with `pause` before each, and another at the end, so that an app
+/// under test gets ticked and redrawn between them before `event_loop` runs out of events.
+#[cfg(test)]
+pub fn scripted(
+    events: Vec<Event>,
+    pause: Duration,
+) -> futures::stream::BoxStream<'static, io::Result<Event>> {
+    let steps = events.into_iter().map(Some).chain(std::iter::once(None));
+    futures::stream::iter(steps)
+        .then(move |event| async move {
+            time::sleep(pause).await;
+            event.map(Ok)
+        })
+        .filter_map(futures::future::ready)
+        .boxed()
+}
+
+/// The rows of what was last drawn to a test terminal.
+#[cfg(test)]
+pub fn drawn_lines(terminal: &Terminal<TestBackend>) -> Vec<String> {
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer.get(x, y).symbol.as_str())
+                .collect()
+        })
+        .collect()
+}
+
+async fn next_tick(ticks: &mut Option<Interval>) {
+    match ticks {
+        Some(ticks) => {
+            ticks.tick().await;
+        }
+        None => futures::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::widgets::Paragraph;
+
+    use super::*;
+
+    /// Shows the keys typed so far, and quits on `q` or after `max_ticks` ticks. Only every
+    /// other tick asks to be redrawn.
+    #[derive(Default)]
+    struct Typist {
+        typed: String,
+        ticks: usize,
+        max_ticks: Option<usize>,
+        draws: usize,
+        suspends: usize,
+    }
+
+    impl WithTui for Typist {}
+
+    impl App for Typist {
+        fn draw<B: Backend>(&mut self, f: &mut Frame<B>) -> Result<(), KitError> {
+            f.render_widget(Paragraph::new(self.typed.as_str()), f.size());
+            self.draws += 1;
+            Ok(())
+        }
+
+        fn handle_event(&mut self, event: Event) -> Result<ControlFlow<()>, KitError> {
+            match event {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('q'),
+                    ..
+                }) => return Ok(ControlFlow::Break(())),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char(c),
+                    ..
+                }) => self.typed.push(c),
+                _ => {}
+            }
+            Ok(ControlFlow::Continue(()))
+        }
+
+        fn tick(&mut self) -> Result<ControlFlow<()>, KitError> {
+            self.ticks += 1;
+            match self.max_ticks {
+                Some(max) if self.ticks >= max => Ok(ControlFlow::Break(())),
+                _ => Ok(ControlFlow::Continue(())),
+            }
+        }
+
+        fn suspend(&mut self) -> Result<(), KitError> {
+            self.suspends += 1;
+            Ok(())
+        }
+
+        fn needs_redraw(&self) -> bool {
+            self.ticks.is_multiple_of(2)
+        }
+
+        fn tick_rate(&self) -> Option<Duration> {
+            self.max_ticks.map(|_| Duration::from_millis(100))
+        }
+    }
+
+    fn key(c: char) -> io::Result<Event> {
+        Ok(Event::Key(KeyEvent::new(
+            KeyCode::Char(c),
+            KeyModifiers::NONE,
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_event_loop_until_quit() {
+        let mut terminal = Terminal::new(TestBackend::new(10, 1)).unwrap();
+        let mut app = Typist::default();
+        let events = futures::stream::iter([key('h'), key('i'), key('q'), key('!')]);
+        event_loop(&mut app, &mut terminal, events).await.unwrap();
+        assert_eq!(app.typed, "hi");
+        assert_eq!(app.ticks, 0);
+        assert_eq!(app.draws, 3);
+        terminal
+            .backend()
+            .assert_buffer(&ratatui::buffer::Buffer::with_lines(vec!["hi        "]));
+    }
+
+    #[tokio::test]
+    async fn test_event_loop_ctrl_z() {
+        let mut terminal = Terminal::new(TestBackend::new(10, 1)).unwrap();
+        let mut app = Typist::default();
+        let ctrl_z = Ok(Event::Key(KeyEvent::new(
+            KeyCode::Char('z'),
+            KeyModifiers::CONTROL,
+        )));
+        let events = futures::stream::iter([key('h'), ctrl_z, key('i'), key('q')]);
+        event_loop(&mut app, &mut terminal, events).await.unwrap();
+        assert_eq!(app.typed, "hi");
+        assert_eq!(app.suspends, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_event_loop_ticks() {
+        let mut terminal = Terminal::new(TestBackend::new(10, 1)).unwrap();
+        let mut app = Typist {
+            max_ticks: Some(5),
+            ..Typist::default()
+        };
+        event_loop(&mut app, &mut terminal, futures::stream::pending())
+            .await
+            .unwrap();
+        assert_eq!(app.ticks, 5);
+        // The first draw, then after ticks 2 and 4
+        assert_eq!(app.draws, 3);
+    }
+}
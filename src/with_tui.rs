@@ -1,31 +1,151 @@
-use std::io::{stdout, Stdout};
+use std::{
+    io::{stdin, stdout, IsTerminal, Stdout},
+    ops::{Deref, DerefMut},
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Once,
+    },
+};
 
 use crossterm::{
-    event::DisableMouseCapture,
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 
+use crate::error::KitError;
+
+static INSTALL_PANIC_HOOK: Once = Once::new();
+static TUI_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether stdin and stdout are both a terminal, as a TUI needs: it reads keys from one and draws
+/// on the other. In a pipe or under CI, raw mode fails and the event stream never ends.
+pub fn is_terminal() -> bool {
+    stdin().is_terminal() && stdout().is_terminal()
+}
+
+/// Put the terminal back into its normal state: cooked mode, main screen, no mouse capture.
+fn restore_terminal() -> Result<(), KitError> {
+    TUI_ACTIVE.store(false, Ordering::SeqCst);
+    disable_raw_mode()?;
+    stdout()
+        .execute(LeaveAlternateScreen)?
+        .execute(DisableMouseCapture)?;
+    Ok(())
+}
+
+/// Give the terminal back to the shell and stop the process, as Ctrl-Z would outside raw mode,
+/// then set it up for the TUI again once the shell continues the process.
+#[cfg(unix)]
+pub fn suspend(mouse: bool) -> Result<(), KitError> {
+    restore_terminal()?;
+    stdout().execute(Show)?;
+    // SIGSTOP, since the event loop catches SIGTSTP to get here. Either one tells the shell
+    // that the job has stopped.
+    unsafe {
+        libc::raise(libc::SIGSTOP);
+    }
+    TUI_ACTIVE.store(true, Ordering::SeqCst);
+    stdout().execute(EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    if mouse {
+        stdout().execute(EnableMouseCapture)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn suspend(_mouse: bool) -> Result<(), KitError> {
+    Ok(())
+}
+
+/// A terminal in TUI mode. Dropping it restores the terminal, so an early return via `?` leaves
+/// the shell usable even if `tui_shutdown` never runs.
+pub struct TuiGuard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+    active: bool,
+}
+
+impl Deref for TuiGuard {
+    type Target = Terminal<CrosstermBackend<Stdout>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl DerefMut for TuiGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TuiGuard {
+    fn drop(&mut self) {
+        if self.active {
+            log::debug!("restoring terminal after an early exit");
+            let _ = self.terminal.show_cursor();
+            let _ = restore_terminal();
+        }
+    }
+}
+
+/// Raw mode on the main screen, for reading single key presses without taking over the display.
+/// Dropping it turns raw mode back off.
+pub struct RawModeGuard;
+
+impl RawModeGuard {
+    pub fn enable() -> std::io::Result<Self> {
+        enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+    }
+}
+
 pub trait WithTui {
-    fn tui_setup(&self) -> Result<Terminal<CrosstermBackend<Stdout>>, Box<dyn std::error::Error>> {
+    fn tui_setup(&self) -> Result<TuiGuard, KitError> {
+        if !is_terminal() {
+            return Err(KitError::Terminal(
+                "the TUI needs a terminal, but stdin or stdout isn't one".to_string(),
+            ));
+        }
+        // Restore the terminal before the previous hook prints the panic message, which would
+        // be garbled or lost on the alternate screen.
+        INSTALL_PANIC_HOOK.call_once(|| {
+            let previous_hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if TUI_ACTIVE.load(Ordering::SeqCst) {
+                    let _ = restore_terminal();
+                }
+                previous_hook(info);
+            }));
+        });
         let backend = CrosstermBackend::new(stdout());
         let mut terminal = Terminal::new(backend)?;
+        TUI_ACTIVE.store(true, Ordering::SeqCst);
         stdout().execute(EnterAlternateScreen)?;
         enable_raw_mode()?;
         terminal.clear()?;
-        Ok(terminal)
+        log::debug!("entered TUI mode");
+        Ok(TuiGuard {
+            terminal,
+            active: true,
+        })
     }
 
-    fn tui_shutdown(
-        &self,
-        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    fn tui_shutdown(&self, terminal: &mut TuiGuard) -> Result<(), KitError> {
+        terminal.active = false;
         terminal.show_cursor()?;
-        disable_raw_mode()?;
-        stdout()
-            .execute(LeaveAlternateScreen)?
-            .execute(DisableMouseCapture)?;
+        restore_terminal()?;
+        log::debug!("left TUI mode");
         Ok(())
     }
 }
@@ -0,0 +1,30 @@
+use kit::{MatchEngine, MatchMode, MatchOptions};
+
+#[test]
+fn test_regex_against_builtin_dictionary() {
+    let engine = MatchEngine::new("qu.z".to_string(), MatchOptions::default());
+    assert_eq!(engine.matching_words().unwrap(), ["quiz"]);
+}
+
+#[test]
+fn test_custom_words() {
+    let words = "Rust\ncrate\ntrace\ncater\nreact\n".to_string();
+    let options = MatchOptions {
+        contains: true,
+        ignore_case: true,
+    };
+    let engine = MatchEngine::new("r.*t".to_string(), options).with_words(words.clone());
+    assert_eq!(engine.matching_words().unwrap(), ["Rust", "crate", "react"]);
+    let anagrams = MatchEngine::with_mode("crate".to_string(), MatchMode::Anagram)
+        .with_words(words)
+        .matching_words()
+        .unwrap();
+    assert_eq!(anagrams, ["cater", "crate", "react", "trace"]);
+}
+
+#[test]
+fn test_invalid_regex() {
+    assert!(MatchEngine::new("(".to_string(), MatchOptions::default())
+        .matches()
+        .is_err());
+}